@@ -3,7 +3,9 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{DeriveInput, Path, Token, parse_str, punctuated::Punctuated};
 
-use crate::{StateAttrs, util::concat};
+#[cfg(feature = "progress")]
+use crate::ProgressEntry;
+use crate::{StateAttrs, TransEntry, util::concat};
 
 pub(crate) fn derive_register_state_helper(input: &DeriveInput, attrs: &StateAttrs) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
@@ -19,6 +21,16 @@ pub(crate) fn derive_register_state_helper(input: &DeriveInput, attrs: &StateAtt
     let crate_setup_path = concat(&crate_path, "setup");
     let register_state_trait = concat(&crate_setup_path, "RegisterState");
 
+    // Construct `StateFlushSchedule`.
+    let flush_in = attrs
+        .flush_in
+        .as_ref()
+        .map(|label| {
+            let flush_schedule_ty = concat(&crate_schedule_path, "StateFlushSchedule");
+            quote! { app.insert_resource(#flush_schedule_ty::<Self>::new(#label)); }
+        })
+        .unwrap_or_default();
+
     // Construct `ResolveStatePlugin`.
     let resolve_state = {
         let bevy_ecs_path = BevyManifest::shared(|manifest| manifest.get_path("bevy_ecs"));
@@ -82,6 +94,22 @@ pub(crate) fn derive_register_state_helper(input: &DeriveInput, attrs: &StateAtt
             attrs.flush_message,
         )
     };
+    let guard_flush = {
+        let crate_guard_flush_path = concat(&crate_schedule_path, "guard_flush");
+        plugin(&crate_guard_flush_path, "GuardFlush", attrs.guard_flush)
+    };
+    let observe_flush = {
+        let crate_observe_flush_path = concat(&crate_schedule_path, "observe_flush");
+        plugin(
+            &crate_observe_flush_path,
+            "ObserveFlush",
+            attrs.observe_flush,
+        )
+    };
+    let sync_flush = {
+        let crate_sync_flush_path = concat(&crate_schedule_path, "sync_flush");
+        plugin(&crate_sync_flush_path, "SyncFlush", attrs.sync_flush)
+    };
     #[cfg(not(feature = "debug"))]
     let log_flush = quote! {};
     #[cfg(feature = "debug")]
@@ -104,6 +132,156 @@ pub(crate) fn derive_register_state_helper(input: &DeriveInput, attrs: &StateAtt
         let crate_react_path = concat(&crate_extra_path, "react");
         plugin(&crate_react_path, "React", attrs.react)
     };
+    #[cfg(not(feature = "substate"))]
+    let substate = quote! {};
+    #[cfg(feature = "substate")]
+    let substate = attrs
+        .substate_of
+        .as_ref()
+        .map(|(parent_ty, parent)| {
+            let crate_pattern_path = concat(&crate_path, "pattern");
+            let fn_pattern_ty = concat(&crate_pattern_path, "FnStatePattern");
+            let crate_substate_path = concat(&crate_extra_path, "substate");
+            let state_plugin_ty = concat(&crate_substate_path, "SubstatePlugin");
+
+            quote! {
+                #state_plugin_ty::<Self, _, _>::new(#fn_pattern_ty::new(
+                    |state: &#parent_ty| matches!(state, #parent),
+                )),
+            }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "computed"))]
+    let computed = quote! {};
+    #[cfg(feature = "computed")]
+    let computed = attrs
+        .compute
+        .as_ref()
+        .map(|compute| {
+            let crate_computed_path = concat(&crate_extra_path, "computed");
+            let state_plugin_ty = concat(&crate_computed_path, "ComputedPlugin");
+            let sources = attrs.computed_from.iter();
+
+            quote! {
+                #state_plugin_ty::<Self, _>::new(
+                    #compute as fn(#(Option<&#sources>),*) -> Option<Self>,
+                ),
+            }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "serde"))]
+    let save = quote! {};
+    #[cfg(feature = "serde")]
+    let save = attrs
+        .save
+        .as_ref()
+        .map(|key| {
+            let crate_save_path = concat(&crate_extra_path, "save");
+            let state_plugin_ty = concat(&crate_save_path, "SaveStatePlugin");
+            let key = key
+                .as_ref()
+                .map(|x| quote! { #x })
+                .unwrap_or_else(|| quote! { ::core::stringify!(#ty_name) });
+            quote! { #state_plugin_ty::<Self>::new(#key), }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "timer"))]
+    let timer = quote! {};
+    #[cfg(feature = "timer")]
+    let timer = {
+        let crate_timer_path = concat(&crate_extra_path, "timer");
+        plugin(&crate_timer_path, "StateTimer", attrs.timer)
+    };
+    #[cfg(not(feature = "transition"))]
+    let transition = quote! {};
+    #[cfg(feature = "transition")]
+    let transition = attrs
+        .transition
+        .as_ref()
+        .map(|(exit, enter)| {
+            let crate_transition_path = concat(&crate_extra_path, "transition");
+            let state_plugin_ty = concat(&crate_transition_path, "TransitionPlugin");
+            let secs = |x: &Option<syn::Expr>| {
+                x.as_ref()
+                    .map(|x| quote! { ::core::time::Duration::from_secs_f32(#x) })
+                    .unwrap_or_else(|| quote! { ::core::time::Duration::ZERO })
+            };
+            let (exit, enter) = (secs(exit), secs(enter));
+            quote! { #state_plugin_ty::<Self>::new(#exit, #enter), }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "barrier"))]
+    let exit_barrier = quote! {};
+    #[cfg(feature = "barrier")]
+    let exit_barrier = attrs
+        .exit_barrier
+        .as_ref()
+        .map(|timeout| {
+            let crate_barrier_path = concat(&crate_extra_path, "barrier");
+            let state_plugin_ty = concat(&crate_barrier_path, "ExitBarrierPlugin");
+            let timeout = timeout
+                .as_ref()
+                .map(|x| quote! { Some(::core::time::Duration::from_secs_f32(#x)) })
+                .unwrap_or_else(|| quote! { None });
+            quote! { #state_plugin_ty::<Self>::new(#timeout), }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "progress"))]
+    let progress = quote! {};
+    #[cfg(feature = "progress")]
+    let progress = attrs
+        .progress
+        .as_ref()
+        .map(|ProgressEntry { loading, next }| {
+            let crate_pattern_path = concat(&crate_path, "pattern");
+            let fn_pattern_ty = concat(&crate_pattern_path, "FnStatePattern");
+            let crate_progress_path = concat(&crate_extra_path, "progress");
+            let state_plugin_ty = concat(&crate_progress_path, "ProgressPlugin");
+
+            quote! {
+                #state_plugin_ty::<Self, _>::new(
+                    #fn_pattern_ty::new(|state: &Self| matches!(state, #loading)),
+                    #next,
+                ),
+            }
+        })
+        .unwrap_or_default();
+    #[cfg(not(feature = "history"))]
+    let history = quote! {};
+    #[cfg(feature = "history")]
+    let history = attrs
+        .history
+        .as_ref()
+        .map(|capacity| {
+            let crate_history_path = concat(&crate_extra_path, "history");
+            let state_plugin_ty = concat(&crate_history_path, "HistoryPlugin");
+            quote! { #state_plugin_ty::<Self>::new(#capacity), }
+        })
+        .unwrap_or_default();
+    let validate_trans = attrs
+        .transitions
+        .as_ref()
+        .map(|transitions| {
+            let crate_pattern_path = concat(&crate_path, "pattern");
+            let fn_trans_pattern_ty = concat(&crate_pattern_path, "FnStateTransPattern");
+            let crate_validate_trans_path = concat(&crate_schedule_path, "validate_trans");
+            let state_plugin_ty = concat(&crate_validate_trans_path, "ValidateTransPlugin");
+
+            let arms = transitions.iter().map(|TransEntry { old, new, guard }| {
+                let guard = guard.as_ref().map(|guard| quote! { if #guard });
+                quote! { (#old, #new) #guard => true, }
+            });
+
+            quote! {
+                #state_plugin_ty::<Self, _>::new(#fn_trans_pattern_ty::new(
+                    |old: &Self, new: &Self| match (old, new) {
+                        #(#arms)*
+                        _ => false,
+                    },
+                )),
+            }
+        })
+        .unwrap_or_default();
     let apply_flush = {
         let crate_apply_flush_path = concat(&crate_schedule_path, "apply_flush");
         plugin(&crate_apply_flush_path, "ApplyFlush", attrs.apply_flush)
@@ -112,14 +290,31 @@ pub(crate) fn derive_register_state_helper(input: &DeriveInput, attrs: &StateAtt
     quote! {
         impl #impl_generics #register_state_trait for #ty_name #ty_generics #where_clause {
             fn register_state(app: &mut #app_ty) {
+                #flush_in
                 app.add_plugins((
-                    #resolve_state
-                    #detect_change
-                    #flush_message
-                    #log_flush
-                    #bevy_state
-                    #react
-                    #apply_flush
+                    (
+                        #resolve_state
+                        #detect_change
+                        #flush_message
+                        #guard_flush
+                        #observe_flush
+                        #sync_flush
+                        #log_flush
+                        #bevy_state
+                        #react
+                    ),
+                    (
+                        #substate
+                        #computed
+                        #save
+                        #history
+                        #timer
+                        #transition
+                        #exit_barrier
+                        #progress
+                        #validate_trans
+                        #apply_flush
+                    ),
                 ));
             }
         }
@@ -7,7 +7,10 @@ mod util;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    DeriveInput, Error, Meta, Path, Result, Token, Type, parse_macro_input, parse_str,
+    Data, DeriveInput, Error, Expr, Ident, Meta, MetaNameValue, Pat, PatPath, Path, Result, Token,
+    Type, TypePath,
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_str,
     punctuated::Punctuated,
 };
 
@@ -73,13 +76,27 @@ fn derive_state_helper(input: &DeriveInput, attrs: &StateAttrs) -> proc_macro2::
 struct StateAttrs {
     next: Option<Type>,
     after: Punctuated<Type, Token![,]>,
+    flush_in: Option<Expr>,
     before: Punctuated<Type, Token![,]>,
+    transitions: Option<Punctuated<TransEntry, Token![,]>>,
+    history: Option<Expr>,
+    transition: Option<(Option<Expr>, Option<Expr>)>,
+    exit_barrier: Option<Option<Expr>>,
+    progress: Option<ProgressEntry>,
+    substate_of: Option<(Type, Pat)>,
+    computed_from: Punctuated<Type, Token![,]>,
+    compute: Option<Expr>,
     no_defaults: bool,
     detect_change: bool,
     flush_message: bool,
+    guard_flush: bool,
+    observe_flush: bool,
     log_flush: bool,
+    sync_flush: bool,
     bevy_state: bool,
     react: bool,
+    save: Option<Option<Expr>>,
+    timer: bool,
     apply_flush: bool,
 }
 
@@ -107,6 +124,95 @@ fn parse_state_attrs(input: &DeriveInput) -> Result<StateAttrs> {
                         .expect("invalid `before` states");
                 }
 
+                Meta::List(meta) if meta.path.is_ident("transitions") => {
+                    let mut transitions = meta
+                        .parse_args_with(Punctuated::<TransEntry, Token![,]>::parse_terminated)
+                        .expect("invalid `transitions` table");
+                    for entry in &mut transitions {
+                        qualify_variants(input, &mut entry.old);
+                        qualify_variants(input, &mut entry.new);
+                    }
+                    state_attrs.transitions = Some(transitions);
+                }
+
+                Meta::List(meta) if meta.path.is_ident("substate_of") => {
+                    let parent = meta
+                        .parse_args_with(Pat::parse_multi)
+                        .expect("invalid `substate_of` pattern");
+                    let parent_ty = pattern_type(&parent).ok_or_else(|| {
+                        Error::new_spanned(&parent, "invalid parent state pattern")
+                    })?;
+                    state_attrs.substate_of = Some((parent_ty, parent));
+                }
+
+                Meta::List(meta) if meta.path.is_ident("computed_from") => {
+                    state_attrs.computed_from = meta
+                        .parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)
+                        .expect("invalid `computed_from` states");
+                }
+
+                Meta::NameValue(meta) if meta.path.is_ident("compute") => {
+                    state_attrs.compute = Some(meta.value);
+                }
+
+                Meta::List(meta) if meta.path.is_ident("history") => {
+                    let capacity = meta
+                        .parse_args::<MetaNameValue>()
+                        .ok()
+                        .filter(|x| x.path.is_ident("capacity"))
+                        .expect("invalid `history` options");
+                    state_attrs.history = Some(capacity.value);
+                }
+
+                Meta::List(meta) if meta.path.is_ident("transition") => {
+                    let options = meta
+                        .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                        .expect("invalid `transition` options");
+                    let (mut exit, mut enter) = (None, None);
+                    for option in options {
+                        if option.path.is_ident("exit") {
+                            exit = Some(option.value);
+                        } else if option.path.is_ident("enter") {
+                            enter = Some(option.value);
+                        } else {
+                            return Err(Error::new_spanned(option, "invalid `transition` option"));
+                        }
+                    }
+                    state_attrs.transition = Some((exit, enter));
+                }
+
+                Meta::List(meta) if meta.path.is_ident("save") => {
+                    let key = meta
+                        .parse_args::<MetaNameValue>()
+                        .ok()
+                        .filter(|x| x.path.is_ident("key"))
+                        .expect("invalid `save` options");
+                    state_attrs.save = Some(Some(key.value));
+                }
+
+                Meta::List(meta) if meta.path.is_ident("exit_barrier") => {
+                    let timeout = meta
+                        .parse_args::<MetaNameValue>()
+                        .ok()
+                        .filter(|x| x.path.is_ident("timeout"))
+                        .expect("invalid `exit_barrier` options");
+                    state_attrs.exit_barrier = Some(Some(timeout.value));
+                }
+
+                Meta::List(meta) if meta.path.is_ident("progress") => {
+                    let mut entry = meta
+                        .parse_args::<ProgressEntry>()
+                        .expect("invalid `progress` options");
+                    qualify_variants(input, &mut entry.loading);
+                    qualify_expr_variants(input, &mut entry.next);
+                    state_attrs.progress = Some(entry);
+                }
+
+                Meta::List(meta) if meta.path.is_ident("flush_in") => {
+                    state_attrs.flush_in =
+                        Some(meta.parse_args().expect("invalid `flush_in` schedule"));
+                }
+
                 Meta::List(meta) if meta.path.is_ident("next") => {
                     state_attrs.next = Some(meta.parse_args().expect("invalid `next` type"));
                 }
@@ -120,9 +226,15 @@ fn parse_state_attrs(input: &DeriveInput) -> Result<StateAttrs> {
                         "no_defaults" => state_attrs.no_defaults = true,
                         "detect_change" => state_attrs.detect_change = true,
                         "flush_message" => state_attrs.flush_message = true,
+                        "guard_flush" => state_attrs.guard_flush = true,
+                        "observe_flush" => state_attrs.observe_flush = true,
                         "log_flush" => state_attrs.log_flush = true,
+                        "sync_flush" => state_attrs.sync_flush = true,
                         "bevy_state" => state_attrs.bevy_state = true,
                         "react" => state_attrs.react = true,
+                        "save" => state_attrs.save = Some(None),
+                        "timer" => state_attrs.timer = true,
+                        "exit_barrier" => state_attrs.exit_barrier = Some(None),
                         "apply_flush" => state_attrs.apply_flush = true,
                         _ => return Err(Error::new_spanned(ident, "invalid state attribute")),
                     }
@@ -133,6 +245,13 @@ fn parse_state_attrs(input: &DeriveInput) -> Result<StateAttrs> {
         }
     }
 
+    if state_attrs.computed_from.is_empty() != state_attrs.compute.is_none() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`computed_from(...)` and `compute = ...` must be used together",
+        ));
+    }
+
     // Enable default options.
     if !state_attrs.no_defaults {
         state_attrs.detect_change = true;
@@ -140,5 +259,125 @@ fn parse_state_attrs(input: &DeriveInput) -> Result<StateAttrs> {
         state_attrs.apply_flush = true;
     }
 
+    // A `transitions(...)` table rejects flushes with a guard.
+    if state_attrs.transitions.is_some() {
+        state_attrs.guard_flush = true;
+    }
+
     Ok(state_attrs)
 }
+
+// An `old => new` or `old => new if guard` entry in a `transitions(...)` table.
+struct TransEntry {
+    old: Pat,
+    new: Pat,
+    guard: Option<Expr>,
+}
+
+impl Parse for TransEntry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let old = Pat::parse_multi(input)?;
+        input.parse::<Token![=>]>()?;
+        let new = Pat::parse_multi(input)?;
+        let guard = if input.parse::<Option<Token![if]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { old, new, guard })
+    }
+}
+
+// A `loading => next` entry in a `progress(...)` option.
+struct ProgressEntry {
+    loading: Pat,
+    next: Expr,
+}
+
+impl Parse for ProgressEntry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let loading = Pat::parse_multi(input)?;
+        input.parse::<Token![=>]>()?;
+        let next = input.parse()?;
+        Ok(Self { loading, next })
+    }
+}
+
+// Qualify a bare enum variant name in a path with `Self::`.
+fn qualify_variant_path(input: &DeriveInput, path: &mut Path) {
+    let Data::Enum(data) = &input.data else {
+        return;
+    };
+    let is_variant = |ident: &Ident| data.variants.iter().any(|x| &x.ident == ident);
+    if path.leading_colon.is_none() && path.get_ident().is_some_and(is_variant) {
+        path.segments.insert(0, parse_str("Self").unwrap());
+    }
+}
+
+// Qualify bare enum variant names in an expression with `Self::` (e.g. `Level(1)` -> `Self::Level(1)`).
+fn qualify_expr_variants(input: &DeriveInput, expr: &mut Expr) {
+    match expr {
+        Expr::Path(x) if x.qself.is_none() => qualify_variant_path(input, &mut x.path),
+        Expr::Call(x) => qualify_expr_variants(input, &mut x.func),
+        Expr::Struct(x) if x.qself.is_none() => qualify_variant_path(input, &mut x.path),
+        Expr::Paren(x) => qualify_expr_variants(input, &mut x.expr),
+        _ => (),
+    }
+}
+
+// Qualify bare enum variant names in a pattern with `Self::` (e.g. `Title` -> `Self::Title`).
+fn qualify_variants(input: &DeriveInput, pat: &mut Pat) {
+    let Data::Enum(data) = &input.data else {
+        return;
+    };
+    let is_variant = |ident: &Ident| data.variants.iter().any(|x| &x.ident == ident);
+    let qualify = |path: &mut Path| qualify_variant_path(input, path);
+
+    match pat {
+        Pat::Ident(x) if x.by_ref.is_none() && x.mutability.is_none() && x.subpat.is_none() => {
+            if is_variant(&x.ident) {
+                let mut path = Path::from(x.ident.clone());
+                qualify(&mut path);
+                *pat = Pat::Path(PatPath {
+                    attrs: x.attrs.clone(),
+                    qself: None,
+                    path,
+                });
+            }
+        }
+        Pat::Path(x) if x.qself.is_none() => qualify(&mut x.path),
+        Pat::TupleStruct(x) if x.qself.is_none() => qualify(&mut x.path),
+        Pat::Struct(x) if x.qself.is_none() => qualify(&mut x.path),
+        Pat::Or(x) => {
+            for case in &mut x.cases {
+                qualify_variants(input, case);
+            }
+        }
+        Pat::Paren(x) => qualify_variants(input, &mut x.pat),
+        _ => (),
+    }
+}
+
+// Infer the state type matched by a pattern (e.g. `Screen::Gameplay` -> `Screen`, `Level(1)` -> `Level`).
+fn pattern_type(pat: &Pat) -> Option<Type> {
+    let path = match pat {
+        Pat::Ident(x) => Path::from(x.ident.clone()),
+        Pat::Path(x) => x.path.clone(),
+        Pat::TupleStruct(x) => x.path.clone(),
+        Pat::Struct(x) => x.path.clone(),
+        Pat::Or(x) => return x.cases.first().and_then(pattern_type),
+        Pat::Paren(x) => return pattern_type(&x.pat),
+        _ => return None,
+    };
+
+    // Strip the variant from an enum variant path (`Type::Variant`).
+    let mut path = path;
+    let is_type_name = |ident: &Ident| ident.to_string().starts_with(char::is_uppercase);
+    let n = path.segments.len();
+    if n >= 2 && is_type_name(&path.segments[n - 2].ident) {
+        path.segments.pop();
+        path.segments.pop_punct();
+    }
+
+    Some(Type::Path(TypePath { qself: None, path }))
+}
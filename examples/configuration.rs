@@ -7,7 +7,8 @@ use pyri_state::{
     prelude::*,
     schedule::{
         apply_flush::ApplyFlushPlugin, detect_change::DetectChangePlugin,
-        flush_message::FlushMessagePlugin, resolve_state::ResolveStatePlugin,
+        flush_message::FlushMessagePlugin, guard_flush::GuardFlushPlugin,
+        observe_flush::ObserveFlushPlugin, resolve_state::ResolveStatePlugin,
     },
     setup::RegisterState,
 };
@@ -35,12 +36,16 @@ struct RawState;
 // The built-in state plugins can be configured:
 #[derive(State, Resource, Reflect, Clone, PartialEq, Eq, Hash, Debug)]
 #[state(
-    // Disable default plugins: detect_change, flush_message, apply_flush.
+    // Disable default plugins: detect_change, flush_message, guard_flush, apply_flush.
     no_defaults,
     // Trigger a flush on any state change (requires PartialEq, Eq).
     detect_change,
     // Write a message on flush (requires Clone).
     flush_message,
+    // Write a message when a guard rejects a flush (requires Clone).
+    guard_flush,
+    // Trigger observer events on flush (requires Clone).
+    observe_flush,
     // Log on flush (requires Debug).
     log_flush,
     // Include a `BevyState<Self>` wrapper (requires StateMut, Clone, PartialEq, Eq, Hash, Debug).
@@ -50,6 +55,10 @@ struct RawState;
     react,
     // Clone the next state into the current state on flush (requires Clone).
     apply_flush,
+    // Record the last 16 flushes in a `StateHistory<Self>` resource (requires Clone).
+    history(capacity = 16),
+    // Block any transition not listed in this table (requires StateMut, Clone, Debug).
+    transitions(DerivedState => DerivedState),
     // Swap out the default `NextStateBuffer<Self>` for another `NextState` type.
     // (see `custom_next_state` example for more information)
     next(NextStateStack<Self>),
@@ -81,6 +90,8 @@ impl RegisterState for CustomState {
                 .after::<DerivedState>(),
             DetectChangePlugin::<Self>::default(),
             FlushMessagePlugin::<Self>::default(),
+            GuardFlushPlugin::<Self>::default(),
+            ObserveFlushPlugin::<Self>::default(),
             LogFlushPlugin::<Self>::default(),
             BevyStatePlugin::<Self>::default(),
             ReactPlugin::<Self>::default(),
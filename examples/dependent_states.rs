@@ -9,18 +9,6 @@ fn main() -> AppExit {
         .init_state::<Screen>()
         .add_state::<CheckerboardSquare>()
         .add_state::<SquareColor>()
-        .add_systems(
-            StateFlush,
-            (
-                // Enable `CheckerboardSquare` only during `Screen::Gameplay`.
-                Screen::Gameplay.on_edge(
-                    CheckerboardSquare::disable,
-                    CheckerboardSquare::enable_default,
-                ),
-                // Compute `SquareColor` from `CheckerboardSquare`.
-                CheckerboardSquare::ANY.on_enter(compute_square_color),
-            ),
-        )
         .run()
 }
 
@@ -34,7 +22,7 @@ enum Screen {
 
 // Substate of `Screen::Gameplay`
 #[derive(State, Resource, Reflect, Clone, PartialEq, Eq, Default)]
-#[state(after(Screen))]
+#[state(substate_of(Screen::Gameplay))]
 #[reflect(Resource)]
 struct CheckerboardSquare {
     row: u8,
@@ -43,19 +31,19 @@ struct CheckerboardSquare {
 
 // Computed from `CheckerboardSquare`
 #[derive(State, Resource, Reflect, Clone, PartialEq, Eq)]
-#[state(after(CheckerboardSquare))]
+#[state(computed_from(CheckerboardSquare), compute = compute_square_color)]
 #[reflect(Resource)]
 enum SquareColor {
     Black,
     White,
 }
 
-fn compute_square_color(board: NextRef<CheckerboardSquare>, mut color: NextMut<SquareColor>) {
-    color.set(board.get().map(|board| {
+fn compute_square_color(board: Option<&CheckerboardSquare>) -> Option<SquareColor> {
+    board.map(|board| {
         if board.row + board.col % 2 == 0 {
             SquareColor::Black
         } else {
             SquareColor::White
         }
-    }));
+    })
 }
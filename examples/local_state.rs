@@ -0,0 +1,66 @@
+//! Store a state per entity as a component (e.g. for per-enemy or per-door state machines).
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use pyri_state::prelude::*;
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((DefaultPlugins, StatePlugin))
+        .add_plugins(LocalStatePlugin::<Door>::default())
+        .add_systems(Startup, spawn_doors)
+        .add_systems(
+            StateFlush,
+            (
+                // Per-entity hooks receive the entity as input.
+                Door::Open.on_local_enter(play_open_sound),
+                Door::Closed.on_local_enter(play_close_sound),
+                Door::ANY_TO_ANY.on_local_trans(log_door_trans),
+            ),
+        )
+        .add_systems(
+            Update,
+            toggle_doors.run_if(input_just_pressed(KeyCode::Space)),
+        )
+        .run()
+}
+
+#[derive(Component, Reflect, Clone, PartialEq, Eq, Debug)]
+#[reflect(Component)]
+enum Door {
+    Open,
+    Closed,
+}
+
+impl LocalState for Door {}
+
+fn spawn_doors(mut commands: Commands) {
+    // Each door will enter its initial state on the next flush.
+    commands.spawn(LocalNextStateBuffer::enabled(Door::Closed));
+    commands.spawn(LocalNextStateBuffer::enabled(Door::Open));
+}
+
+fn toggle_doors(mut door_query: Query<LocalFlushMut<Door>>) {
+    for mut door in &mut door_query {
+        if door.current.is_in(&Door::Open) {
+            door.enter(Door::Closed);
+        } else {
+            door.enter(Door::Open);
+        }
+    }
+}
+
+fn play_open_sound(entity: In<Entity>) {
+    info!("{} opened", *entity);
+}
+
+fn play_close_sound(entity: In<Entity>) {
+    info!("{} closed", *entity);
+}
+
+fn log_door_trans(entity: In<Entity>, door_query: Query<LocalFlushRef<Door>>) {
+    let Ok(door) = door_query.get(*entity) else {
+        return;
+    };
+    let (old, new) = door.unwrap();
+    info!("{} transitioned: {old:?} -> {new:?}", *entity);
+}
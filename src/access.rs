@@ -15,7 +15,7 @@ use bevy_ecs::system::{Res, ResMut, StaticSystemParam, SystemParam};
 
 use crate::{
     next_state::{NextState, NextStateMut, TriggerStateFlush},
-    pattern::{StatePattern, StateTransPattern},
+    pattern::{StateFlushPattern, StatePattern, StateTransPattern},
     state::{State, StateMut},
 };
 
@@ -384,6 +384,12 @@ impl<S: State> FlushRef<'_, '_, S> {
     pub fn will_trans<P: StateTransPattern<S>>(&self, pattern: &P) -> bool {
         matches!(self.get(), (Some(x), Some(y)) if pattern.matches(x, y))
     }
+
+    /// Check if `S` will undergo a flush that matches a specific pattern if triggered.
+    pub fn will_flush<P: StateFlushPattern<S>>(&self, pattern: &P) -> bool {
+        let (x, y) = self.get();
+        pattern.matches(x, y)
+    }
 }
 
 /// A [`SystemParam`] with read-only and mutable access to the current and next values of the [`State`] type `S`,
@@ -486,6 +492,12 @@ impl<S: StateMut> FlushMut<'_, '_, S> {
         matches!(self.get(), (Some(x), Some(y)) if pattern.matches(x, y))
     }
 
+    /// Check if `S` will undergo a flush that matches a specific pattern if triggered.
+    pub fn will_flush<P: StateFlushPattern<S>>(&self, pattern: &P) -> bool {
+        let (x, y) = self.get();
+        pattern.matches(x, y)
+    }
+
     /// Disable the next state.
     pub fn disable(&mut self) {
         self.next.disable();
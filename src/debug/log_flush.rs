@@ -9,7 +9,7 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
@@ -20,7 +20,7 @@ mod app {
 
     impl<S: State + Debug> Plugin for LogFlushPlugin<S> {
         fn build(&self, app: &mut App) {
-            schedule_log_flush::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_log_flush::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
@@ -82,7 +82,8 @@ fn log_state_enter<S: State + Debug>(frame: Res<FrameCount>, new: NextRef<S>) {
 pub fn schedule_log_flush<S: State + Debug>(schedule: &mut Schedule) {
     schedule.add_systems((
         log_state_flush::<S>
-            .after(ResolveStateSystems::<S>::Trigger)
+            .in_set(ResolveStateSystems::<S>::Resolve)
+            .after(ResolveStateSystems::<S>::Hold)
             .before(ResolveStateSystems::<S>::Flush)
             .run_if(
                 S::is_triggered
@@ -0,0 +1,368 @@
+//! Hold a pending state flush with named [`ExitBarrier`] counters.
+//!
+//! Enable the `barrier` feature flag to use this module.
+//!
+//! While an `ExitBarrier<S>` is held, a triggered flush of the [`State`] type `S` will stay
+//! pending across frames instead of being applied. Once every hold is released (or the barrier
+//! times out), the pending flush will be applied.
+//!
+//! The timeout is measured with [`Time<Virtual>`] once per frame, even if the
+//! [`StateFlush`](crate::schedule::StateFlush) schedule runs several times per frame.
+//!
+//! Use the `exit_barrier` or `exit_barrier(timeout = ...)`
+//! [derive macro option](pyri_state_derive::State) to add an `ExitBarrier`, then hold and release
+//! it from your systems:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! #[state(exit_barrier(timeout = 10.0))]
+//! enum Screen {
+//!     Title,
+//!     Gameplay,
+//! }
+//!
+//! fn start_save(mut barrier: ResMut<ExitBarrier<Screen>>) {
+//!     barrier.hold("save in progress");
+//!     // ...
+//! }
+//!
+//! fn finish_save(mut barrier: ResMut<ExitBarrier<Screen>>) {
+//!     // ...
+//!     barrier.release("save in progress");
+//! }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds an [`ExitBarrier<S>`] resource and barrier systems for the [`State`]
+    /// type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `exit_barrier` or `exit_barrier(timeout = ...)`
+    /// [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`schedule_exit_barrier<S>`].
+    pub struct ExitBarrierPlugin<S: State> {
+        timeout: Option<Duration>,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: State> Plugin for ExitBarrierPlugin<S> {
+        fn build(&self, app: &mut App) {
+            app.insert_resource(ExitBarrier::<S>::new(self.timeout));
+            schedule_exit_barrier::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State> Default for ExitBarrierPlugin<S> {
+        fn default() -> Self {
+            Self::new(None)
+        }
+    }
+
+    impl<S: State> ExitBarrierPlugin<S> {
+        /// Create an [`ExitBarrierPlugin`] with an optional timeout.
+        pub fn new(timeout: Option<Duration>) -> Self {
+            Self {
+                timeout,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use alloc::{borrow::Cow, collections::BTreeMap};
+use core::{marker::PhantomData, time::Duration};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{IntoScheduleConfigs as _, Schedule},
+    system::{Res, ResMut},
+};
+use bevy_time::{Time, Virtual};
+
+use crate::{
+    access::CurrentRef, next_state::TriggerStateFlush, schedule::ResolveStateSystems, state::State,
+};
+
+/// A resource that holds a pending flush of the [`State`] type `S` while any of its named
+/// counters are above zero.
+///
+/// Added by [`ExitBarrierPlugin<S>`].
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct ExitBarrier<S: State> {
+    holds: BTreeMap<Cow<'static, str>, usize>,
+    timeout: Option<Duration>,
+    held_for: Duration,
+    pending: bool,
+    retriggered: bool,
+    last_update: Duration,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    _phantom: PhantomData<S>,
+}
+
+impl<S: State> Default for ExitBarrier<S> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl<S: State> ExitBarrier<S> {
+    /// Create a new released `ExitBarrier` with an optional timeout.
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            holds: BTreeMap::new(),
+            timeout,
+            held_for: Duration::ZERO,
+            pending: false,
+            retriggered: false,
+            last_update: Duration::ZERO,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Increment the counter for a named blocker.
+    pub fn hold(&mut self, name: impl Into<Cow<'static, str>>) {
+        *self.holds.entry(name.into()).or_default() += 1;
+    }
+
+    /// Decrement the counter for a named blocker.
+    pub fn release(&mut self, name: impl Into<Cow<'static, str>>) {
+        let name = name.into();
+        let Some(count) = self.holds.get_mut(&name) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.holds.remove(&name);
+        }
+    }
+
+    /// Reset the counters for all named blockers.
+    pub fn release_all(&mut self) {
+        self.holds.clear();
+    }
+
+    /// Get the counter for a named blocker.
+    pub fn count(&self, name: &str) -> usize {
+        self.holds.get(name).copied().unwrap_or_default()
+    }
+
+    /// Iterate over the named blockers with their counters.
+    pub fn blockers(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.holds
+            .iter()
+            .map(|(name, &count)| (name.as_ref(), count))
+    }
+
+    /// Check if any named blocker is holding the barrier.
+    pub fn is_held(&self) -> bool {
+        !self.holds.is_empty()
+    }
+
+    /// Check if a flush is currently being held by the barrier.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Get how long the pending flush has been held.
+    pub fn held_for(&self) -> Duration {
+        self.held_for
+    }
+
+    /// Get the timeout after which a pending flush will be applied regardless.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Set the timeout after which a pending flush will be applied regardless.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Check if the pending flush has been held for longer than the timeout.
+    pub fn is_timed_out(&self) -> bool {
+        self.timeout.is_some_and(|timeout| self.held_for >= timeout)
+    }
+}
+
+fn retrigger_flush<S: State>(
+    time: Res<Time<Virtual>>,
+    mut barrier: ResMut<ExitBarrier<S>>,
+    mut trigger: ResMut<TriggerStateFlush<S>>,
+) {
+    // Only advance once per frame.
+    let now = time.elapsed();
+    let delta = now.saturating_sub(barrier.last_update);
+    barrier.last_update = now;
+
+    if !barrier.pending {
+        return;
+    }
+
+    // Cancel the pending flush if it was not seen by the barrier (e.g. rejected by a guard).
+    if barrier.retriggered {
+        barrier.pending = false;
+        barrier.retriggered = false;
+        barrier.held_for = Duration::ZERO;
+        return;
+    }
+
+    barrier.retriggered = true;
+    barrier.held_for += delta;
+    trigger.0 = true;
+}
+
+pub(crate) fn hold_flush<S: State>(
+    state: CurrentRef<S>,
+    mut barrier: ResMut<ExitBarrier<S>>,
+    mut trigger: ResMut<TriggerStateFlush<S>>,
+) {
+    barrier.retriggered = false;
+    if !barrier.is_held() || state.is_disabled() {
+        barrier.pending = false;
+        barrier.held_for = Duration::ZERO;
+        return;
+    }
+
+    if barrier.is_timed_out() {
+        #[cfg(feature = "debug")]
+        {
+            let ty = core::any::type_name::<S>();
+            let held_for = barrier.held_for;
+            bevy_log::warn!("{ty} exit barrier timed out after {held_for:?}, applying flush");
+        }
+        barrier.pending = false;
+        barrier.held_for = Duration::ZERO;
+        return;
+    }
+
+    #[cfg(feature = "debug")]
+    if !barrier.pending {
+        let ty = core::any::type_name::<S>();
+        let blockers = barrier
+            .blockers()
+            .map(|(name, count)| alloc::format!("{name} ({count})"))
+            .collect::<alloc::vec::Vec<_>>()
+            .join(", ");
+        bevy_log::debug!("{ty} flush held by exit barrier: {blockers}");
+    }
+    barrier.pending = true;
+    trigger.0 = false;
+}
+
+/// Add [`ExitBarrier<S>`] systems for the [`State`] type `S` to a schedule.
+///
+/// A held flush will be re-triggered in [`ResolveStateSystems::<S>::Compute`] every frame, and
+/// held again in [`ResolveStateSystems::<S>::Hold`] until the barrier is released or times out.
+/// The barrier holds the flush after any guards have had a chance to reject it, and before a
+/// [`TransitionProgress<S>`](crate::extra::transition::TransitionProgress) starts exiting.
+///
+/// Used in [`ExitBarrierPlugin<S>`].
+pub fn schedule_exit_barrier<S: State>(schedule: &mut Schedule) {
+    schedule.add_systems((
+        retrigger_flush::<S>.in_set(ResolveStateSystems::<S>::Compute),
+        hold_flush::<S>
+            .run_if(S::is_triggered)
+            .in_set(ResolveStateSystems::<S>::Hold),
+    ));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::world::Mut;
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(exit_barrier(timeout = 1.0))]
+    enum Screen {
+        #[default]
+        Title,
+        Gameplay,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Time<Virtual>>()
+            .init_state::<Screen>();
+        app.update();
+        app
+    }
+
+    fn advance(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    fn barrier(app: &mut App) -> Mut<'_, ExitBarrier<Screen>> {
+        app.world_mut().resource_mut::<ExitBarrier<Screen>>()
+    }
+
+    #[test]
+    fn flush_is_held_until_release() {
+        let mut app = app();
+        barrier(&mut app).hold("save");
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert!(barrier(&mut app).is_pending());
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+
+        advance(&mut app, 0.5);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+
+        barrier(&mut app).release("save");
+        app.update();
+        assert!(!barrier(&mut app).is_pending());
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+    }
+
+    #[test]
+    fn timeout_is_measured_once_per_frame() {
+        let mut app = app();
+        barrier(&mut app).hold("save");
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(0.5));
+        for _ in 0..4 {
+            app.world_mut().run_schedule(StateFlush);
+        }
+        app.update();
+        assert_eq!(barrier(&mut app).held_for(), Duration::from_secs_f32(0.5));
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+
+        advance(&mut app, 0.5);
+        assert!(barrier(&mut app).is_held());
+        assert!(!barrier(&mut app).is_pending());
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+    }
+}
@@ -103,12 +103,12 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
     /// A plugin that adds [`BevyState<S>`] propagation systems for the
-    /// [`State`] type `S` to the [`StateFlush`] schedule.
+    /// [`State`] type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
     ///
     /// Calls [`schedule_bevy_state<S>`].
     pub struct BevyStatePlugin<S: StateMut + Clone + PartialEq + Eq + Hash + Debug>(PhantomData<S>);
@@ -116,7 +116,7 @@ mod app {
     impl<S: StateMut + Clone + PartialEq + Eq + Hash + Debug> Plugin for BevyStatePlugin<S> {
         fn build(&self, app: &mut App) {
             bevy::AppExtStates::init_state::<BevyState<S>>(app);
-            schedule_bevy_state::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_bevy_state::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
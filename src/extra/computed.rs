@@ -0,0 +1,258 @@
+//! Compute a state from other states whenever any of them flush.
+//!
+//! Enable the `computed` feature flag to use this module.
+//!
+//! Use the `computed_from(...)` and `compute = ...`
+//! [derive macro options](pyri_state_derive::State) to configure a computed state:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq, Default)]
+//! struct Health(u32);
+//!
+//! #[derive(State, Resource, Clone, PartialEq, Eq, Default)]
+//! struct Shield(u32);
+//!
+//! #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! #[state(computed_from(Health, Shield), compute = compute_vulnerable)]
+//! struct Vulnerable;
+//!
+//! fn compute_vulnerable(health: Option<&Health>, shield: Option<&Shield>) -> Option<Vulnerable> {
+//!     (health?.0 < 10 && shield?.0 == 0).then_some(Vulnerable)
+//! }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::{any::type_name, marker::PhantomData};
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::{flush_schedule_label, flush_schedule_mut};
+
+    use super::*;
+
+    /// A plugin that computes the [`State`] type `S` from its source `State` types in the
+    /// [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `computed_from(...)` and `compute = ...`
+    /// [derive macro options](pyri_state_derive::State).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `S` does not flush in the same schedule as its already-registered source `State`
+    /// types.
+    ///
+    /// Calls [`schedule_computed<S>`].
+    pub struct ComputedPlugin<S: StateMut, F: ComputeStateFn<S>> {
+        compute: F,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: StateMut, F: ComputeStateFn<S>> Plugin for ComputedPlugin<S, F> {
+        fn build(&self, app: &mut App) {
+            let label = flush_schedule_label::<S>(app.world());
+            assert!(
+                self.compute.sources_flush_in(app.world(), label),
+                "{} must flush in the same schedule as its source states ({label:?})",
+                type_name::<S>(),
+            );
+
+            schedule_computed::<S>(flush_schedule_mut::<S>(app), self.compute);
+        }
+    }
+
+    impl<S: StateMut, F: ComputeStateFn<S>> ComputedPlugin<S, F> {
+        /// Create a [`ComputedPlugin`] from a compute function.
+        pub fn new(compute: F) -> Self {
+            Self {
+                compute,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use bevy_ecs::{
+    schedule::{InternedScheduleLabel, IntoScheduleConfigs as _, Schedule},
+    system::Res,
+    world::World,
+};
+
+use crate::{
+    access::{NextMut, NextRef},
+    next_state::TriggerStateFlush,
+    schedule::{ResolveStateSystems, flush_schedule_label},
+    state::{State, StateMut},
+};
+
+/// A function that computes the [`State`] type `S` from the next states of its source `State`
+/// types, e.g. `fn(Option<&A>, Option<&B>) -> Option<S>`.
+///
+/// Implemented for function pointers with up to 8 source `State` types.
+pub trait ComputeStateFn<S: StateMut>: 'static + Send + Sync + Copy {
+    /// Configure `S` to be computed from its source `State` types in a schedule.
+    fn schedule(self, schedule: &mut Schedule);
+
+    /// Check if every source `State` type flushes in the schedule `label`.
+    fn sources_flush_in(self, world: &World, label: InternedScheduleLabel) -> bool;
+}
+
+macro_rules! impl_compute_state_fn {
+    ($(($T:ident, $t:ident)),+) => {
+        impl<S: StateMut, $($T: State),+> ComputeStateFn<S> for fn($(Option<&$T>),+) -> Option<S> {
+            fn schedule(self, schedule: &mut Schedule) {
+                $(schedule.configure_sets(
+                    ResolveStateSystems::<S>::Resolve.after(ResolveStateSystems::<$T>::Resolve),
+                );)+
+
+                schedule.add_systems(
+                    (move |$($t: NextRef<$T>,)+ mut state: NextMut<S>| {
+                        state.set(self($($t.get()),+));
+                    })
+                    .run_if(|$($t: Res<TriggerStateFlush<$T>>),+| false $(|| $t.0)+)
+                    .in_set(ResolveStateSystems::<S>::Compute),
+                );
+            }
+
+            fn sources_flush_in(self, world: &World, label: InternedScheduleLabel) -> bool {
+                true $(&& flush_schedule_label::<$T>(world) == label)+
+            }
+        }
+    };
+}
+
+impl_compute_state_fn!((A, a));
+impl_compute_state_fn!((A, a), (B, b));
+impl_compute_state_fn!((A, a), (B, b), (C, c));
+impl_compute_state_fn!((A, a), (B, b), (C, c), (D, d));
+impl_compute_state_fn!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_compute_state_fn!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+impl_compute_state_fn!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+impl_compute_state_fn!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h)
+);
+
+/// Configure the [`State`] type `S` to be computed from its source `State` types in a schedule.
+///
+/// `S` will be resolved after its sources, and its next state will be set in
+/// [`ResolveStateSystems::<S>::Compute`] whenever any of its sources are triggered to flush.
+///
+/// Used in [`ComputedPlugin<S, F>`].
+pub fn schedule_computed<S: StateMut>(schedule: &mut Schedule, compute: impl ComputeStateFn<S>) {
+    compute.schedule(schedule);
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::{resource::Resource, schedule::ScheduleLabel};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Health(u32);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Shield(u32);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    #[state(computed_from(Health, Shield), compute = compute_vulnerable)]
+    struct Vulnerable(u32);
+
+    fn compute_vulnerable(health: Option<&Health>, shield: Option<&Shield>) -> Option<Vulnerable> {
+        let health = health?.0;
+        (health < 10 && shield?.0 == 0).then_some(Vulnerable(health))
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_state::<Health>()
+            .init_state::<Shield>()
+            .add_state::<Vulnerable>();
+        app
+    }
+
+    #[test]
+    fn recomputes_when_any_source_changes() {
+        let mut app = app();
+        app.update();
+        assert_eq!(app.world().state::<Vulnerable>(), Some(&Vulnerable(0)));
+
+        app.world_mut().set_next_state(Some(Shield(5)));
+        app.update();
+        assert_eq!(app.world().state::<Vulnerable>(), None);
+
+        app.world_mut().set_next_state(Some(Shield(0)));
+        app.world_mut().set_next_state(Some(Health(3)));
+        app.update();
+        assert_eq!(app.world().state::<Vulnerable>(), Some(&Vulnerable(3)));
+
+        app.world_mut().set_next_state(Some(Health(20)));
+        app.update();
+        assert_eq!(app.world().state::<Vulnerable>(), None);
+    }
+
+    #[test]
+    fn disabled_source_disables_computed_state() {
+        let mut app = app();
+        app.update();
+        assert_eq!(app.world().state::<Vulnerable>(), Some(&Vulnerable(0)));
+
+        app.world_mut().set_next_state::<Shield>(None);
+        app.update();
+        assert_eq!(app.world().state::<Shield>(), None);
+        assert_eq!(app.world().state::<Vulnerable>(), None);
+    }
+
+    #[test]
+    fn computes_after_sources_resolve_in_the_same_frame() {
+        let mut app = app();
+        // `Health` is only decided while it resolves, so `Vulnerable` must be computed afterwards.
+        app.add_systems(
+            StateFlush,
+            (|mut health: NextMut<Health>| health.enter(Health(7)))
+                .in_set(ResolveStateSystems::<Health>::Compute),
+        );
+        app.update();
+        assert_eq!(app.world().state::<Health>(), Some(&Health(7)));
+        assert_eq!(app.world().state::<Vulnerable>(), Some(&Vulnerable(7)));
+    }
+
+    #[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
+    struct Custom;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    #[state(flush_in(Custom), computed_from(Health), compute = compute_remote)]
+    struct Remote;
+
+    fn compute_remote(health: Option<&Health>) -> Option<Remote> {
+        health.map(|_| Remote)
+    }
+
+    #[test]
+    #[should_panic(expected = "must flush in the same schedule as its source states")]
+    fn sources_in_another_schedule_panic() {
+        App::new()
+            .add_plugins(StatePlugin)
+            .init_state::<Health>()
+            .add_state::<Remote>();
+    }
+}
@@ -0,0 +1,408 @@
+//! Record applied state flushes in a bounded [`StateHistory`] log, and undo / redo them.
+//!
+//! Enable the `history` feature flag to use this module.
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds a [`StateHistory<S>`] resource and a recording system for the
+    /// [`State`] type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `history(capacity = N)` [derive macro option](pyri_state_derive::State).
+    ///
+    /// Records are timestamped with the [`FrameCount`] and [`Time`] resources if they exist
+    /// (e.g. via `FrameCountPlugin` and `TimePlugin`).
+    ///
+    /// Calls [`schedule_history<S>`].
+    pub struct HistoryPlugin<S: State + Clone + Eq> {
+        capacity: usize,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: State + Clone + Eq> Plugin for HistoryPlugin<S> {
+        fn build(&self, app: &mut App) {
+            app.insert_resource(StateHistory::<S>::new(self.capacity));
+            schedule_history::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State + Clone + Eq> HistoryPlugin<S> {
+        /// Create a [`HistoryPlugin`] that keeps up to `capacity` records.
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                capacity,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use alloc::collections::VecDeque;
+use core::{marker::PhantomData, time::Duration};
+
+use bevy_diagnostic::FrameCount;
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{IntoScheduleConfigs as _, Schedule},
+    system::{Res, ResMut},
+};
+use bevy_time::Time;
+use tiny_bail::prelude::*;
+
+use crate::{
+    access::{FlushRef, NextMut},
+    schedule::ResolveStateSystems,
+    state::{State, StateMut},
+};
+
+/// A single applied flush of the [`State`] type `S`, stored in [`StateHistory<S>`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct StateRecord<S: State> {
+    /// The state before the flush, or `None` if disabled.
+    pub old: Option<S>,
+    /// The state after the flush, or `None` if disabled.
+    pub new: Option<S>,
+    /// The [`FrameCount`] when the flush occurred, or 0 if there is no `FrameCount` resource.
+    pub frame: u32,
+    /// The elapsed [`Time`] when the flush occurred, or zero if there is no `Time` resource.
+    pub elapsed: Duration,
+}
+
+/// A resource that records the most recent flushes of the [`State`] type `S` in a ring buffer.
+///
+/// Added by [`HistoryPlugin<S>`].
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct StateHistory<S: State> {
+    records: VecDeque<StateRecord<S>>,
+    capacity: usize,
+    cursor: usize,
+    /// The cursor and next state of a pending undo or redo.
+    travel: Option<(usize, Option<S>)>,
+}
+
+impl<S: State> StateHistory<S> {
+    /// Create a new empty `StateHistory` that keeps up to `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            cursor: 0,
+            travel: None,
+        }
+    }
+
+    /// Get the maximum number of records.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Get the number of records that have not been undone.
+    ///
+    /// Records at or after this index can be redone.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Check if there is a record that can be undone.
+    pub fn can_undo(&self) -> bool {
+        self.travel_cursor() > 0
+    }
+
+    /// Check if there is a record that can be redone.
+    pub fn can_redo(&self) -> bool {
+        self.travel_cursor() < self.records.len()
+    }
+
+    /// Get the cursor after any pending undo or redo.
+    fn travel_cursor(&self) -> usize {
+        self.travel
+            .as_ref()
+            .map_or(self.cursor, |(cursor, _)| *cursor)
+    }
+
+    /// Get the record at a specific index, from oldest to newest.
+    pub fn get(&self, index: usize) -> Option<&StateRecord<S>> {
+        self.records.get(index)
+    }
+
+    /// Get the most recent record.
+    pub fn last(&self) -> Option<&StateRecord<S>> {
+        self.records.back()
+    }
+
+    /// Iterate over the records from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &StateRecord<S>> {
+        self.records.iter()
+    }
+
+    /// Add a new record, discarding any undone records and the oldest record if at capacity.
+    pub fn push(&mut self, record: StateRecord<S>) {
+        self.records.truncate(self.cursor);
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+        self.cursor = self.records.len();
+    }
+
+    /// Remove all records.
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.cursor = 0;
+        self.travel = None;
+    }
+}
+
+/// An extension trait for [`StateMut`] types that also implement [`Clone`] and have a
+/// [`StateHistory`].
+///
+/// An undo or redo is recorded as a cursor move instead of a new record once `S` flushes to the
+/// undone or redone state, even if that happens on a later frame or a later flush in the same
+/// frame. If `S` flushes to any other state first (e.g. because the undo or redo was rejected by
+/// a guard), the undo or redo is discarded and the flush is recorded as usual.
+pub trait StateMutExtHistory: StateMut + Clone + Eq {
+    /// A system that sets the next state to undo the most recent record in [`StateHistory`]
+    /// and triggers a flush.
+    fn undo(mut state: NextMut<Self>, mut history: ResMut<StateHistory<Self>>) {
+        let cursor = r!(history.travel_cursor().checked_sub(1));
+        let old = r!(history.records.get(cursor)).old.clone();
+        history.travel = Some((cursor, old.clone()));
+        state.trigger().set(old);
+    }
+
+    /// A system that sets the next state to redo the most recently undone record in
+    /// [`StateHistory`] and triggers a flush.
+    fn redo(mut state: NextMut<Self>, mut history: ResMut<StateHistory<Self>>) {
+        let cursor = history.travel_cursor();
+        let new = r!(history.records.get(cursor)).new.clone();
+        history.travel = Some((cursor + 1, new.clone()));
+        state.trigger().set(new);
+    }
+}
+
+impl<S: StateMut + Clone + Eq> StateMutExtHistory for S {}
+
+fn record_flush<S: State + Clone + Eq>(
+    frame: Option<Res<FrameCount>>,
+    time: Option<Res<Time>>,
+    state: FlushRef<S>,
+    mut history: ResMut<StateHistory<S>>,
+) {
+    // Move the cursor instead of recording if this flush is an undo or redo.
+    let (old, new) = state.get();
+    if let Some((cursor, target)) = history.travel.take()
+        && target.as_ref() == new
+    {
+        history.cursor = cursor;
+        return;
+    }
+
+    history.push(StateRecord {
+        old: old.cloned(),
+        new: new.cloned(),
+        frame: frame.map_or(0, |x| x.0),
+        elapsed: time.map_or(Duration::ZERO, |x| x.elapsed()),
+    });
+}
+
+/// Add a [`StateHistory<S>`] recording system for the [`State`] type `S` to a schedule.
+///
+/// Used in [`HistoryPlugin<S>`].
+pub fn schedule_history<S: State + Clone + Eq>(schedule: &mut Schedule) {
+    schedule.add_systems(record_flush::<S>.in_set(ResolveStateSystems::<S>::AnyFlush));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use bevy_app::App;
+    use bevy_ecs::system::{IntoSystem, RunSystemOnce as _};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(guard_flush, history(capacity = 8))]
+    enum Level {
+        #[default]
+        A,
+        B,
+        C,
+    }
+
+    #[derive(Resource, Default)]
+    struct UndoInFlush(bool);
+
+    #[derive(Resource, Default)]
+    struct Allow(bool);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<UndoInFlush>()
+            .insert_resource(Allow(true))
+            .init_state::<Level>()
+            .add_systems(
+                StateFlush,
+                (
+                    Level::ANY_TO_ANY.guard(|allow: Res<Allow>| allow.0),
+                    // Undo in response to a flush, within the same pass.
+                    Level::undo
+                        .run_if(|undo: Res<UndoInFlush>| undo.0)
+                        .in_set(ResolveStateSystems::<Level>::Applied),
+                ),
+            );
+        app.update();
+        for level in [Level::B, Level::C] {
+            app.world_mut().set_next_state(Some(level));
+            app.update();
+        }
+        assert_eq!(history(&app).len(), 3);
+        // Without `FrameCount` or `Time` resources, records fall back to zero.
+        let last = history(&app).last().unwrap();
+        assert_eq!((last.frame, last.elapsed), (0, Duration::ZERO));
+        app
+    }
+
+    fn history(app: &App) -> &StateHistory<Level> {
+        app.world().resource::<StateHistory<Level>>()
+    }
+
+    fn run<M>(app: &mut App, system: impl IntoSystem<(), (), M>) {
+        app.world_mut().run_system_once(system).unwrap();
+    }
+
+    #[test]
+    fn undo_and_redo_move_the_cursor() {
+        let mut app = app();
+        run(&mut app, Level::undo);
+        app.update();
+        run(&mut app, Level::undo);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level::A));
+        assert_eq!(history(&app).cursor(), 1);
+
+        run(&mut app, Level::redo);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level::B));
+        assert_eq!(history(&app).cursor(), 2);
+        assert_eq!(history(&app).len(), 3);
+        assert!(history(&app).can_redo());
+    }
+
+    #[test]
+    fn undo_during_flush_is_kept() {
+        let mut app = app();
+        app.world_mut().resource_mut::<UndoInFlush>().0 = true;
+        app.world_mut().set_next_state(Some(Level::A));
+        app.update();
+        app.world_mut().resource_mut::<UndoInFlush>().0 = false;
+        assert_eq!(app.world().state::<Level>(), Some(&Level::A));
+        assert_eq!(history(&app).cursor(), 4);
+
+        // Flush the undo in an extra pass within the same frame.
+        app.world_mut().run_schedule(StateFlush);
+        assert_eq!(app.world().state::<Level>(), Some(&Level::C));
+        assert_eq!(history(&app).cursor(), 3);
+        assert_eq!(history(&app).len(), 4);
+        assert!(history(&app).can_redo());
+    }
+
+    #[test]
+    fn rejected_undo_is_discarded() {
+        let mut app = app();
+        app.world_mut().resource_mut::<Allow>().0 = false;
+        run(&mut app, Level::undo);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level::C));
+
+        app.world_mut().resource_mut::<Allow>().0 = true;
+        app.world_mut().set_next_state(Some(Level::A));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level::A));
+        assert_eq!(history(&app).cursor(), 4);
+        assert_eq!(history(&app).len(), 4);
+    }
+
+    #[test]
+    fn new_flush_discards_undone_records() {
+        let mut app = app();
+        run(&mut app, Level::undo);
+        app.update();
+        assert!(history(&app).can_redo());
+
+        app.world_mut().set_next_state(Some(Level::A));
+        app.update();
+        assert_eq!(history(&app).len(), 3);
+        assert_eq!(history(&app).cursor(), 3);
+        assert!(!history(&app).can_redo());
+        let last = history(&app).last().unwrap();
+        assert_eq!((&last.old, &last.new), (&Some(Level::B), &Some(Level::A)));
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(history(capacity = 2))]
+    struct Score(usize);
+
+    #[test]
+    fn oldest_records_are_evicted_within_one_frame() {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<FrameCount>()
+            .add_state::<Score>();
+        app.world_mut().resource_mut::<FrameCount>().0 = 7;
+
+        // Flush several times in the same frame.
+        for score in 1..=3 {
+            app.world_mut().set_next_state(Some(Score(score)));
+            app.world_mut().trigger_state::<Score>();
+            app.world_mut().flush_state::<Score>();
+        }
+
+        let history = app.world().resource::<StateHistory<Score>>();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.cursor(), 2);
+        let records = history
+            .iter()
+            .map(|x| (x.old.clone(), x.new.clone(), x.frame))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                (Some(Score(1)), Some(Score(2)), 7),
+                (Some(Score(2)), Some(Score(3)), 7),
+            ],
+        );
+    }
+}
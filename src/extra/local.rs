@@ -0,0 +1,995 @@
+//! Store per-entity [`LocalState`] types as components.
+//!
+//! Enable the `local` feature flag to use this module.
+//!
+//! # Example
+//!
+//! Implement [`LocalState`] for a component type:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(Component, Clone, PartialEq, Eq)]
+//! enum Door {
+//!     Open,
+//!     Closed,
+//! }
+//!
+//! impl LocalState for Door {}
+//! ```
+//!
+//! Add the [`LocalStatePlugin`] for `Door` and add per-entity flush hooks:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! # #[derive(Component, Clone, PartialEq, Eq)]
+//! # enum Door {
+//! #     Open,
+//! #     Closed,
+//! # }
+//! #
+//! # impl LocalState for Door {}
+//! #
+//! fn play_door_sound(entity: In<Entity>) {
+//!     info!("Door {} opened", *entity);
+//! }
+//!
+//! # fn plugin(app: &mut App) {
+//! app.add_plugins(LocalStatePlugin::<Door>::default());
+//! app.add_systems(StateFlush, Door::Open.on_enter(play_door_sound));
+//! # }
+//! ```
+//!
+//! Spawn an entity with a [`LocalNextStateBuffer`] to enable `Door` on the next flush:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! # #[derive(Component, Clone, PartialEq, Eq)]
+//! # enum Door {
+//! #     Open,
+//! #     Closed,
+//! # }
+//! #
+//! # impl LocalState for Door {}
+//! #
+//! fn spawn_door(mut commands: Commands) {
+//!     commands.spawn(LocalNextStateBuffer::enabled(Door::Closed));
+//! }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::StateFlush;
+
+    use super::*;
+
+    /// A plugin that adds per-entity flush systems for the [`LocalState`] type `S`
+    /// to the [`StateFlush`] schedule.
+    ///
+    /// Calls [`schedule_local_state<S>`].
+    pub struct LocalStatePlugin<S: LocalState + Clone + Eq>(PhantomData<S>);
+
+    impl<S: LocalState + Clone + Eq> Plugin for LocalStatePlugin<S> {
+        fn build(&self, app: &mut App) {
+            schedule_local_state::<S>(
+                app.init_schedule(StateFlush)
+                    .get_schedule_mut(StateFlush)
+                    .unwrap(),
+            );
+        }
+    }
+
+    impl<S: LocalState + Clone + Eq> Default for LocalStatePlugin<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+}
+
+use alloc::vec::Vec;
+use core::{convert::Infallible, fmt::Debug, hash::Hash, marker::PhantomData};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_ecs::{
+    component::{Component, Mutable},
+    entity::Entity,
+    query::QueryData,
+    schedule::{IntoScheduleConfigs, Schedule, ScheduleConfigs, SystemSet},
+    system::{Commands, In, IntoSystem, Query, ScheduleSystem, SystemId, SystemState},
+    world::World,
+};
+use tiny_bail::prelude::*;
+
+use crate::{
+    pattern::{
+        AndPattern, AndTransPattern, AnyStatePattern, AnyStateTransPattern, FnStatePattern,
+        FnStateTransPattern, NotPattern, NotTransPattern, OrPattern, OrTransPattern,
+    },
+    schedule::{ApplyFlushSystems, is_flush_allowed},
+};
+
+/// A [`Component`] that can be used as a per-entity state.
+///
+/// The current state is stored as the component itself, and the next state is stored
+/// in a [`LocalNextStateBuffer<Self>`] component on the same entity.
+///
+/// Use [`LocalStatePlugin<Self>`] to resolve state flushes for every entity with this state type.
+pub trait LocalState: Component<Mutability = Mutable> + Sized {
+    /// The [`AnyStatePattern`] for this state type.
+    const ANY: AnyStatePattern<Self> = AnyStatePattern(PhantomData);
+
+    /// The [`AnyStateTransPattern`] for this state type.
+    const ANY_TO_ANY: AnyStateTransPattern<Self> = AnyStateTransPattern(PhantomData);
+
+    /// Create a [`FnStatePattern`] from a callback.
+    fn with<F>(f: F) -> FnStatePattern<Self, F>
+    where
+        F: 'static + Send + Sync + Fn(&Self) -> bool,
+    {
+        FnStatePattern::new(f)
+    }
+
+    /// Create a [`FnStateTransPattern`] from a callback.
+    fn when<F>(f: F) -> FnStateTransPattern<Self, F>
+    where
+        F: 'static + Send + Sync + Fn(&Self, &Self) -> bool,
+    {
+        FnStateTransPattern::new(f)
+    }
+}
+
+/// A [`Component`] that stores the next value of the [`LocalState`] type `S` on the same entity.
+#[derive(Component, Debug)]
+#[require(LocalTriggerStateFlush<S>)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Component)
+)]
+pub struct LocalNextStateBuffer<S: LocalState>(
+    /// The next state, or `None` if disabled.
+    pub Option<S>,
+);
+
+impl<S: LocalState> LocalNextStateBuffer<S> {
+    /// Create a disabled `LocalNextStateBuffer`.
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    /// Create an enabled `LocalNextStateBuffer` with a specific value.
+    pub fn enabled(state: S) -> Self {
+        Self(Some(state))
+    }
+}
+
+/// A [`Component`] that determines whether the [`LocalState`] type `S` will flush on the
+/// same entity in the [`StateFlush`](crate::schedule::StateFlush) schedule.
+#[derive(Component, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Component)
+)]
+pub struct LocalTriggerStateFlush<S: LocalState>(
+    /// The flush flag. If true, `S` will flush on this entity in the
+    /// [`StateFlush`](crate::schedule::StateFlush) schedule.
+    pub bool,
+    PhantomData<S>,
+);
+
+impl<S: LocalState> Default for LocalTriggerStateFlush<S> {
+    fn default() -> Self {
+        Self(false, PhantomData)
+    }
+}
+
+/// A [`QueryData`] with read-only access to the current value of the [`LocalState`] type `S`.
+#[derive(QueryData)]
+pub struct LocalCurrentRef<S: LocalState> {
+    /// The current state, or `None` if disabled.
+    pub current: Option<&'static S>,
+}
+
+impl<S: LocalState> LocalCurrentRefItem<'_, '_, S> {
+    /// Get a read-only reference to the current state, or `None` if disabled.
+    pub fn get(&self) -> Option<&S> {
+        self.current
+    }
+
+    /// Get a read-only reference to the current state, or panic if disabled.
+    pub fn unwrap(&self) -> &S {
+        self.get().unwrap()
+    }
+
+    /// Check if the current state is disabled.
+    pub fn is_disabled(&self) -> bool {
+        self.current.is_none()
+    }
+
+    /// Check if the current state is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Check if the current state is enabled and matches a specific [`LocalStatePattern`].
+    pub fn is_in<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), Some(x) if pattern.matches(x))
+    }
+}
+
+/// A [`QueryData`] with read-only access to the next value of the [`LocalState`] type `S`.
+#[derive(QueryData)]
+pub struct LocalNextRef<S: LocalState> {
+    /// The next state.
+    pub next: &'static LocalNextStateBuffer<S>,
+    /// The flush trigger.
+    pub trigger: &'static LocalTriggerStateFlush<S>,
+}
+
+impl<S: LocalState> LocalNextRefItem<'_, '_, S> {
+    /// Get a read-only reference to the next state, or `None` if disabled.
+    pub fn get(&self) -> Option<&S> {
+        self.next.0.as_ref()
+    }
+
+    /// Get a read-only reference to the next state, or panic if disabled.
+    pub fn unwrap(&self) -> &S {
+        self.get().unwrap()
+    }
+
+    /// Check if the next state will be disabled.
+    pub fn will_be_disabled(&self) -> bool {
+        self.get().is_none()
+    }
+
+    /// Check if the next state will be enabled.
+    pub fn will_be_enabled(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Check if the next state will be enabled and match a specific [`LocalStatePattern`].
+    pub fn will_be_in<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), Some(x) if pattern.matches(x))
+    }
+
+    /// Check if `S` is triggered to flush on this entity.
+    pub fn is_triggered(&self) -> bool {
+        self.trigger.0
+    }
+}
+
+/// A [`QueryData`] with mutable access to the next value of the [`LocalState`] type `S`.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct LocalNextMut<S: LocalState> {
+    /// The next state.
+    pub next: &'static mut LocalNextStateBuffer<S>,
+    /// The flush trigger.
+    pub trigger: &'static mut LocalTriggerStateFlush<S>,
+}
+
+impl<S: LocalState> LocalNextMutItem<'_, '_, S> {
+    /// Get a read-only reference to the next state, or `None` if disabled.
+    pub fn get(&self) -> Option<&S> {
+        self.next.0.as_ref()
+    }
+
+    /// Get a mutable reference to the next state, or `None` if disabled.
+    pub fn get_mut(&mut self) -> Option<&mut S> {
+        self.next.0.as_mut()
+    }
+
+    /// Set the next state to a new value, or `None` to disable.
+    pub fn set(&mut self, state: Option<S>) {
+        self.next.0 = state;
+    }
+
+    /// Get a read-only reference to the next state, or panic if disabled.
+    pub fn unwrap(&self) -> &S {
+        self.get().unwrap()
+    }
+
+    /// Get a mutable reference to the next state, or panic if disabled.
+    pub fn unwrap_mut(&mut self) -> &mut S {
+        self.get_mut().unwrap()
+    }
+
+    /// Check if the next state will be disabled.
+    pub fn will_be_disabled(&self) -> bool {
+        self.get().is_none()
+    }
+
+    /// Check if the next state will be enabled.
+    pub fn will_be_enabled(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Check if the next state will be enabled and match a specific [`LocalStatePattern`].
+    pub fn will_be_in<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), Some(x) if pattern.matches(x))
+    }
+
+    /// Check if `S` is triggered to flush on this entity.
+    pub fn is_triggered(&self) -> bool {
+        self.trigger.0
+    }
+
+    /// Trigger `S` to flush on this entity.
+    pub fn trigger(&mut self) -> &mut Self {
+        self.trigger.0 = true;
+        self
+    }
+
+    /// Reset the trigger for `S` to flush on this entity.
+    pub fn reset_trigger(&mut self) -> &mut Self {
+        self.trigger.0 = false;
+        self
+    }
+
+    /// Disable the next state.
+    pub fn disable(&mut self) {
+        self.set(None);
+    }
+
+    /// Enable the next state with a specific value if it's disabled.
+    pub fn enable(&mut self, value: S) {
+        if self.will_be_disabled() {
+            self.enter(value);
+        }
+    }
+
+    /// Toggle the next state between disabled and enabled with a specific value.
+    pub fn toggle(&mut self, value: S) {
+        if self.will_be_disabled() {
+            self.enter(value);
+        } else {
+            self.disable();
+        }
+    }
+
+    /// Enable the next state with a specific value.
+    pub fn enter(&mut self, value: S) {
+        self.set(Some(value));
+    }
+}
+
+/// A [`QueryData`] with read-only access to the current and next values of the
+/// [`LocalState`] type `S`.
+#[derive(QueryData)]
+pub struct LocalFlushRef<S: LocalState> {
+    /// A query with read-only access to the current state.
+    pub current: LocalCurrentRef<S>,
+    /// A query with read-only access to the next state.
+    pub next: LocalNextRef<S>,
+}
+
+impl<S: LocalState + Eq> LocalFlushRefItem<'_, '_, S> {
+    /// Check if `S` will refresh in a state that matches a specific pattern if triggered.
+    pub fn will_refresh<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(
+            self.get(),
+            (Some(x), Some(y)) if x == y && pattern.matches(y),
+        )
+    }
+
+    /// Check if `S` will change if triggered.
+    pub fn will_change(&self) -> bool {
+        matches!(self.get(), (x, y) if x != y)
+    }
+}
+
+impl<S: LocalState> LocalFlushRefItem<'_, '_, S> {
+    /// Get read-only references to the current and next states, or `None` if disabled.
+    pub fn get(&self) -> (Option<&S>, Option<&S>) {
+        (self.current.get(), self.next.get())
+    }
+
+    /// Get read-only references to the current and next states, or panic if disabled.
+    pub fn unwrap(&self) -> (&S, &S) {
+        let (current, next) = self.get();
+        (current.unwrap(), next.unwrap())
+    }
+
+    /// Check if `S` is triggered to flush on this entity.
+    pub fn is_triggered(&self) -> bool {
+        self.next.is_triggered()
+    }
+
+    /// Check if `S` will exit a state that matches a specific pattern if triggered.
+    pub fn will_exit<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), (Some(x), _) if pattern.matches(x))
+    }
+
+    /// Check if `S` will become disabled from a state that matches a specific pattern if triggered.
+    pub fn will_disable<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), (Some(x), None) if pattern.matches(x))
+    }
+
+    /// Check if `S` will enter a state that matches a specific pattern if triggered.
+    pub fn will_enter<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), (_, Some(y)) if pattern.matches(y))
+    }
+
+    /// Check if `S` will become enabled in a state that matches a specific pattern if triggered.
+    pub fn will_enable<P: LocalStatePattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), (None, Some(y)) if pattern.matches(y))
+    }
+
+    /// Check if `S` will undergo a transition that matches a specific pattern if triggered.
+    pub fn will_trans<P: LocalStateTransPattern<S>>(&self, pattern: &P) -> bool {
+        matches!(self.get(), (Some(x), Some(y)) if pattern.matches(x, y))
+    }
+}
+
+/// A [`QueryData`] with read-only and mutable access to the current and next values of the
+/// [`LocalState`] type `S`, respectively.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct LocalFlushMut<S: LocalState> {
+    /// A query with read-only access to the current state.
+    pub current: LocalCurrentRef<S>,
+    /// A query with mutable access to the next state.
+    pub next: LocalNextMut<S>,
+}
+
+impl<S: LocalState + Clone> LocalFlushMutItem<'_, '_, S> {
+    /// Set the next state to remain in the current state with no flush.
+    pub fn reset(&mut self) {
+        let current = self.current.get().cloned();
+        self.next.reset_trigger().set(current);
+    }
+
+    /// Set the next state to flush to the current state.
+    pub fn refresh(&mut self) {
+        let current = self.current.get().cloned();
+        self.next.trigger().set(current);
+    }
+}
+
+impl<S: LocalState> LocalFlushMutItem<'_, '_, S> {
+    /// Get read-only references to the current and next states, or `None` if disabled.
+    pub fn get(&self) -> (Option<&S>, Option<&S>) {
+        (self.current.get(), self.next.get())
+    }
+
+    /// Get a read-only and mutable reference to the current and next state respectively, or `None` if disabled.
+    pub fn get_mut(&mut self) -> (Option<&S>, Option<&mut S>) {
+        (self.current.get(), self.next.get_mut())
+    }
+
+    /// Get read-only references to the current and next states, or panic if disabled.
+    pub fn unwrap(&self) -> (&S, &S) {
+        (self.current.unwrap(), self.next.unwrap())
+    }
+
+    /// Get a read-only and mutable reference to the current and next state respectively, or panic if disabled.
+    pub fn unwrap_mut(&mut self) -> (&S, &mut S) {
+        (self.current.unwrap(), self.next.unwrap_mut())
+    }
+
+    /// Disable the next state.
+    pub fn disable(&mut self) {
+        self.next.disable();
+    }
+
+    /// Enable the next state with a specific value if the current state is disabled.
+    pub fn enable(&mut self, value: S) {
+        if self.current.is_disabled() {
+            self.enter(value);
+        }
+    }
+
+    /// Set the next state to a toggle of the current state between disabled and enabled
+    /// with a specific value.
+    pub fn toggle(&mut self, value: S) {
+        if self.current.is_disabled() {
+            self.enter(value);
+        } else {
+            self.disable();
+        }
+    }
+
+    /// Enable the next state with a specific value.
+    pub fn enter(&mut self, value: S) {
+        self.next.enter(value);
+    }
+
+    /// Trigger `S` to flush on this entity.
+    pub fn trigger(&mut self) -> &mut Self {
+        self.next.trigger();
+        self
+    }
+
+    /// Reset the trigger for `S` to flush on this entity.
+    pub fn reset_trigger(&mut self) -> &mut Self {
+        self.next.reset_trigger();
+        self
+    }
+}
+
+/// A type that can match a subset of values of the [`LocalState`] type `S`.
+///
+/// This mirrors [`StatePattern`](crate::pattern::StatePattern) for local states. If `S`
+/// implements `Eq`, it can be used directly as a local state pattern.
+///
+/// Unlike the global hooks, [`on_exit`](Self::on_exit) and [`on_enter`](Self::on_enter) run once
+/// for each matching entity and receive the [`Entity`] as input. If a type implements both
+/// [`State`](crate::state::State) and `LocalState`, call them as
+/// `LocalStatePattern::on_enter(pattern, system)` to disambiguate.
+pub trait LocalStatePattern<S: LocalState>: 'static + Send + Sync + Sized {
+    /// Check if the pattern matches a particular state.
+    fn matches(&self, state: &S) -> bool;
+
+    /// Configure a system to run for each entity that exits a matching state.
+    ///
+    /// The system receives the [`Entity`] as input.
+    fn on_exit<M: 'static>(
+        self,
+        system: impl IntoSystem<In<Entity>, (), M> + 'static,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        local_hook(
+            move |state: &LocalFlushRefItem<S>| state.will_exit(&self),
+            system,
+        )
+        .in_set(ResolveLocalStateSystems::<S>::Exit)
+    }
+
+    /// Configure a system to run for each entity that enters a matching state.
+    ///
+    /// The system receives the [`Entity`] as input.
+    fn on_enter<M: 'static>(
+        self,
+        system: impl IntoSystem<In<Entity>, (), M> + 'static,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        local_hook(
+            move |state: &LocalFlushRefItem<S>| state.will_enter(&self),
+            system,
+        )
+        .in_set(ResolveLocalStateSystems::<S>::Enter)
+    }
+}
+
+impl<S: LocalState + Eq> LocalStatePattern<S> for S {
+    fn matches(&self, state: &S) -> bool {
+        self == state
+    }
+}
+
+impl<S: LocalState> LocalStatePattern<S> for AnyStatePattern<S> {
+    fn matches(&self, _state: &S) -> bool {
+        true
+    }
+}
+
+impl<S: LocalState, F> LocalStatePattern<S> for FnStatePattern<S, F>
+where
+    F: 'static + Send + Sync + Fn(&S) -> bool,
+{
+    fn matches(&self, state: &S) -> bool {
+        (self.0)(state)
+    }
+}
+
+impl<S: LocalState, P1: LocalStatePattern<S>, P2: LocalStatePattern<S>> LocalStatePattern<S>
+    for AndPattern<P1, P2>
+{
+    fn matches(&self, state: &S) -> bool {
+        self.0.matches(state) && self.1.matches(state)
+    }
+}
+
+impl<S: LocalState, P1: LocalStatePattern<S>, P2: LocalStatePattern<S>> LocalStatePattern<S>
+    for OrPattern<P1, P2>
+{
+    fn matches(&self, state: &S) -> bool {
+        self.0.matches(state) || self.1.matches(state)
+    }
+}
+
+impl<S: LocalState, P: LocalStatePattern<S>> LocalStatePattern<S> for NotPattern<P> {
+    fn matches(&self, state: &S) -> bool {
+        !self.0.matches(state)
+    }
+}
+
+/// A type that can match a subset of transitions in the [`LocalState`] type `S`.
+///
+/// This mirrors [`StateTransPattern`](crate::pattern::StateTransPattern) for local states.
+pub trait LocalStateTransPattern<S: LocalState>: 'static + Send + Sync + Sized {
+    /// Check if the pattern matches a particular pair of states.
+    fn matches(&self, old: &S, new: &S) -> bool;
+
+    /// Configure a system to run for each entity that undergoes a matching transition.
+    ///
+    /// The system receives the [`Entity`] as input.
+    fn on_trans<M: 'static>(
+        self,
+        system: impl IntoSystem<In<Entity>, (), M> + 'static,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        local_hook(
+            move |state: &LocalFlushRefItem<S>| state.will_trans(&self),
+            system,
+        )
+        .in_set(ResolveLocalStateSystems::<S>::Trans)
+    }
+}
+
+impl<S: LocalState, P1: LocalStatePattern<S>, P2: LocalStatePattern<S>> LocalStateTransPattern<S>
+    for (P1, P2)
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        self.0.matches(old) && self.1.matches(new)
+    }
+}
+
+impl<S: LocalState> LocalStateTransPattern<S> for AnyStateTransPattern<S> {
+    fn matches(&self, _old: &S, _new: &S) -> bool {
+        true
+    }
+}
+
+impl<S: LocalState, F> LocalStateTransPattern<S> for FnStateTransPattern<S, F>
+where
+    F: 'static + Send + Sync + Fn(&S, &S) -> bool,
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        (self.0)(old, new)
+    }
+}
+
+impl<S: LocalState, P1: LocalStateTransPattern<S>, P2: LocalStateTransPattern<S>>
+    LocalStateTransPattern<S> for AndTransPattern<P1, P2>
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        self.0.matches(old, new) && self.1.matches(old, new)
+    }
+}
+
+impl<S: LocalState, P1: LocalStateTransPattern<S>, P2: LocalStateTransPattern<S>>
+    LocalStateTransPattern<S> for OrTransPattern<P1, P2>
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        self.0.matches(old, new) || self.1.matches(old, new)
+    }
+}
+
+impl<S: LocalState, P: LocalStateTransPattern<S>> LocalStateTransPattern<S> for NotTransPattern<P> {
+    fn matches(&self, old: &S, new: &S) -> bool {
+        !self.0.matches(old, new)
+    }
+}
+
+// Build an exclusive system that runs `system` once for each triggered entity that passes `condition`.
+fn local_hook<S: LocalState, M: 'static>(
+    condition: impl 'static + Send + Sync + Fn(&LocalFlushRefItem<S>) -> bool,
+    system: impl IntoSystem<In<Entity>, (), M> + 'static,
+) -> impl FnMut(&mut World, &mut SystemState<Query<(Entity, LocalFlushRef<S>)>>) {
+    let mut system = Some(IntoSystem::into_system(system));
+    let mut system_id: Option<SystemId<In<Entity>>> = None;
+
+    move |world, query| {
+        let entities = r!(query.get(world))
+            .iter()
+            .filter(|(_, state)| state.is_triggered() && condition(state))
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        if entities.is_empty() {
+            return;
+        }
+
+        let system_id =
+            *system_id.get_or_insert_with(|| world.register_system(system.take().unwrap()));
+        for entity in entities {
+            c!(world.run_system_with(system_id, entity));
+        }
+    }
+}
+
+/// A suite of system sets in the [`StateFlush`](crate::schedule::StateFlush)
+/// schedule for each [`LocalState`] type `S`.
+///
+/// Configured by [`LocalStatePlugin<S>`] as follows:
+///
+/// 1. [`Resolve`](Self::Resolve) (before [`ApplyFlushSystems`], and if allowed by the
+///    [`StateFlushFilter`](crate::schedule::StateFlushFilter))
+///     1. [`Compute`](Self::Compute)
+///     2. [`Trigger`](Self::Trigger)
+///     3. [`Flush`](Self::Flush)
+///         1. [`Exit`](Self::Exit)
+///         2. [`Trans`](Self::Trans)
+///         3. [`Enter`](Self::Enter)
+#[derive(SystemSet)]
+pub enum ResolveLocalStateSystems<S: LocalState> {
+    /// Resolve the state flush logic for `S`.
+    Resolve,
+    /// Optionally compute the next value for `S`.
+    Compute,
+    /// Decide whether to trigger a flush for `S`.
+    Trigger,
+    /// Run per-entity on-flush hooks for `S`.
+    Flush,
+    /// Run per-entity on-exit hooks for `S`.
+    Exit,
+    /// Run per-entity on-transition hooks for `S`.
+    Trans,
+    /// Run per-entity on-enter hooks for `S`.
+    Enter,
+    #[doc(hidden)]
+    _PhantomData(PhantomData<S>, Infallible),
+}
+
+impl<S: LocalState> Clone for ResolveLocalStateSystems<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Resolve => Self::Resolve,
+            Self::Compute => Self::Compute,
+            Self::Trigger => Self::Trigger,
+            Self::Flush => Self::Flush,
+            Self::Exit => Self::Exit,
+            Self::Trans => Self::Trans,
+            Self::Enter => Self::Enter,
+            Self::_PhantomData(..) => unreachable!(),
+        }
+    }
+}
+
+impl<S: LocalState> PartialEq for ResolveLocalStateSystems<S> {
+    fn eq(&self, other: &Self) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+}
+
+impl<S: LocalState> Eq for ResolveLocalStateSystems<S> {}
+
+impl<S: LocalState> Hash for ResolveLocalStateSystems<S> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+    }
+}
+
+impl<S: LocalState> Debug for ResolveLocalStateSystems<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Resolve => write!(f, "Resolve"),
+            Self::Compute => write!(f, "Compute"),
+            Self::Trigger => write!(f, "Trigger"),
+            Self::Flush => write!(f, "Flush"),
+            Self::Exit => write!(f, "Exit"),
+            Self::Trans => write!(f, "Trans"),
+            Self::Enter => write!(f, "Enter"),
+            Self::_PhantomData(..) => unreachable!(),
+        }
+    }
+}
+
+fn detect_change_local<S: LocalState + Eq>(mut state_query: Query<LocalFlushMut<S>>) {
+    for mut state in &mut state_query {
+        if !state.next.is_triggered() && state.current.get() != state.next.get() {
+            state.trigger();
+        }
+    }
+}
+
+fn apply_flush_local<S: LocalState + Clone>(
+    mut commands: Commands,
+    mut state_query: Query<(Entity, Option<&mut S>, LocalNextMut<S>)>,
+) {
+    for (entity, current, mut next) in &mut state_query {
+        if !next.is_triggered() {
+            continue;
+        }
+
+        match (current, next.get()) {
+            (Some(mut x), Some(y)) => *x = y.clone(),
+            (Some(_), None) => {
+                commands.entity(entity).remove::<S>();
+            }
+            (None, Some(y)) => {
+                commands.entity(entity).insert(y.clone());
+            }
+            _ => (),
+        }
+        next.reset_trigger();
+    }
+}
+
+/// Add per-entity flush systems for the [`LocalState`] type `S` to a schedule.
+///
+/// Used in [`LocalStatePlugin<S>`].
+pub fn schedule_local_state<S: LocalState + Clone + Eq>(schedule: &mut Schedule) {
+    schedule.configure_sets((
+        ResolveLocalStateSystems::<S>::Resolve
+            .before(ApplyFlushSystems)
+            .run_if(is_flush_allowed::<S>),
+        (
+            ResolveLocalStateSystems::<S>::Compute,
+            ResolveLocalStateSystems::<S>::Trigger,
+            ResolveLocalStateSystems::<S>::Flush,
+        )
+            .chain()
+            .in_set(ResolveLocalStateSystems::<S>::Resolve),
+        (
+            ResolveLocalStateSystems::<S>::Exit,
+            ResolveLocalStateSystems::<S>::Trans,
+            ResolveLocalStateSystems::<S>::Enter,
+        )
+            .chain()
+            .in_set(ResolveLocalStateSystems::<S>::Flush),
+    ));
+
+    schedule.add_systems((
+        detect_change_local::<S>.in_set(ResolveLocalStateSystems::<S>::Trigger),
+        apply_flush_local::<S>
+            .run_if(is_flush_allowed::<S>)
+            .in_set(ApplyFlushSystems),
+    ));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::{resource::Resource, system::ResMut};
+
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[derive(Component, Clone, PartialEq, Eq, Debug)]
+    enum Door {
+        Open,
+        Closed,
+    }
+
+    impl LocalState for Door {}
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<(&'static str, Entity)>);
+
+    fn push(name: &'static str) -> impl FnMut(In<Entity>, ResMut<Log>) {
+        move |In(entity), mut log| log.0.push((name, entity))
+    }
+
+    #[test]
+    fn local_hooks_run_per_entity() {
+        let mut app = App::new();
+        app.add_plugins((StatePlugin, LocalStatePlugin::<Door>::default()))
+            .init_resource::<Log>()
+            .add_systems(
+                StateFlush,
+                (
+                    Door::Open.on_enter(push("open")),
+                    Door::with(|x| *x == Door::Closed).on_exit(push("unlatch")),
+                    (Door::Closed, Door::Open).on_trans(push("creak")),
+                ),
+            );
+
+        let a = app
+            .world_mut()
+            .spawn(LocalNextStateBuffer::enabled(Door::Closed))
+            .id();
+        let b = app
+            .world_mut()
+            .spawn(LocalNextStateBuffer::enabled(Door::Open))
+            .id();
+        app.update();
+        assert_eq!(app.world().get::<Door>(a), Some(&Door::Closed));
+        assert_eq!(app.world_mut().resource_mut::<Log>().0, [("open", b)]);
+
+        app.world_mut().resource_mut::<Log>().0.clear();
+        app.world_mut()
+            .get_mut::<LocalNextStateBuffer<Door>>(a)
+            .unwrap()
+            .0 = Some(Door::Open);
+        app.update();
+        assert_eq!(app.world().get::<Door>(a), Some(&Door::Open));
+        let mut log = core::mem::take(&mut app.world_mut().resource_mut::<Log>().0);
+        log.sort();
+        assert_eq!(log, [("creak", a), ("open", a), ("unlatch", a)]);
+    }
+
+    #[test]
+    fn local_hooks_track_entities_in_different_states() {
+        let mut app = App::new();
+        app.add_plugins((StatePlugin, LocalStatePlugin::<Door>::default()))
+            .init_resource::<Log>()
+            .add_systems(
+                StateFlush,
+                (
+                    Door::Open.on_exit(push("close")),
+                    (Door::Open, Door::Closed).on_trans(push("slam")),
+                    Door::Closed.on_enter(push("closed")),
+                ),
+            );
+
+        let doors = [Door::Open, Door::Closed, Door::Open].map(|door| {
+            app.world_mut()
+                .spawn(LocalNextStateBuffer::enabled(door))
+                .id()
+        });
+        app.update();
+        assert_eq!(
+            app.world_mut().resource_mut::<Log>().0,
+            [("closed", doors[1])]
+        );
+
+        // Close the first door and disable the third door.
+        app.world_mut().resource_mut::<Log>().0.clear();
+        app.world_mut()
+            .get_mut::<LocalNextStateBuffer<Door>>(doors[0])
+            .unwrap()
+            .0 = Some(Door::Closed);
+        app.world_mut()
+            .get_mut::<LocalNextStateBuffer<Door>>(doors[2])
+            .unwrap()
+            .0 = None;
+        app.update();
+        let mut log = core::mem::take(&mut app.world_mut().resource_mut::<Log>().0);
+        log.sort();
+        let mut expected = vec![
+            ("close", doors[0]),
+            ("close", doors[2]),
+            ("closed", doors[0]),
+            ("slam", doors[0]),
+        ];
+        expected.sort();
+        assert_eq!(log, expected);
+        assert_eq!(app.world().get::<Door>(doors[0]), Some(&Door::Closed));
+        assert_eq!(app.world().get::<Door>(doors[1]), Some(&Door::Closed));
+        assert_eq!(app.world().get::<Door>(doors[2]), None);
+    }
+
+    #[test]
+    fn despawning_during_flush_skips_later_hooks() {
+        let mut app = App::new();
+        app.add_plugins((StatePlugin, LocalStatePlugin::<Door>::default()))
+            .init_resource::<Log>()
+            .add_systems(
+                StateFlush,
+                (
+                    Door::Open.on_exit(|In(entity): In<Entity>, mut commands: Commands| {
+                        commands.entity(entity).despawn();
+                    }),
+                    (Door::ANY, Door::ANY).on_trans(push("trans")),
+                    Door::Closed.on_enter(push("closed")),
+                ),
+            );
+
+        let a = app
+            .world_mut()
+            .spawn(LocalNextStateBuffer::enabled(Door::Open))
+            .id();
+        let b = app
+            .world_mut()
+            .spawn(LocalNextStateBuffer::enabled(Door::Closed))
+            .id();
+        app.update();
+        app.world_mut().resource_mut::<Log>().0.clear();
+
+        app.world_mut()
+            .get_mut::<LocalNextStateBuffer<Door>>(a)
+            .unwrap()
+            .0 = Some(Door::Closed);
+        app.world_mut()
+            .get_mut::<LocalNextStateBuffer<Door>>(b)
+            .unwrap()
+            .0 = Some(Door::Open);
+        app.update();
+        assert!(app.world().get_entity(a).is_err());
+        assert_eq!(app.world().get::<Door>(b), Some(&Door::Open));
+        assert_eq!(app.world_mut().resource_mut::<Log>().0, [("trans", b)]);
+    }
+}
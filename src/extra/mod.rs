@@ -1,8 +1,26 @@
 //! Extra tools behind feature flags.
 
+#[cfg(feature = "barrier")]
+pub mod barrier;
 #[cfg(feature = "bevy_state")]
 pub mod bevy_state;
+#[cfg(feature = "computed")]
+pub mod computed;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "local")]
+pub mod local;
+#[cfg(feature = "progress")]
+pub mod progress;
 #[cfg(feature = "react")]
 pub mod react;
+#[cfg(feature = "serde")]
+pub mod save;
 #[cfg(feature = "split")]
 pub mod split;
+#[cfg(feature = "substate")]
+pub mod substate;
+#[cfg(feature = "timer")]
+pub mod timer;
+#[cfg(feature = "transition")]
+pub mod transition;
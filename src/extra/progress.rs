@@ -0,0 +1,411 @@
+//! Track loading progress with a [`ProgressTracker`], and enter a new state once it completes.
+//!
+//! Enable the `progress` feature flag to use this module.
+//!
+//! While the [`State`] type `S` is in a loading state, systems report their [`Progress`] to the
+//! `ProgressTracker<S>` every frame. Once all reported progress is complete, `S` will enter the
+//! configured next state. At least one report is required, so a frame with no reports never
+//! counts as complete.
+//!
+//! Use the `progress(loading => next)` [derive macro option](pyri_state_derive::State) to
+//! configure the loading state, then report progress from your systems:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! #[state(progress(Loading => Gameplay))]
+//! enum Screen {
+//!     Title,
+//!     Loading,
+//!     Gameplay,
+//! }
+//!
+//! fn load_level(mut tracker: ResMut<ProgressTracker<Screen>>) {
+//!     // ...
+//!     tracker.report((3, 10));
+//! }
+//!
+//! fn load_audio() -> Progress {
+//!     // ...
+//!     Progress::from(true)
+//! }
+//!
+//! # fn plugin(app: &mut App) {
+//! app.add_systems(Update, Screen::Loading.on_update((
+//!     load_level,
+//!     load_audio.pipe(report_progress::<Screen>),
+//! )));
+//! # }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds a [`ProgressTracker<S>`] resource and progress systems for the
+    /// [`State`] type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `progress(loading => next)` [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`schedule_progress<S, P>`].
+    pub struct ProgressPlugin<S: StateMut + Clone + Eq, P: StatePattern<S> + Clone> {
+        loading: P,
+        next: S,
+    }
+
+    impl<S: StateMut + Clone + Eq, P: StatePattern<S> + Clone> Plugin for ProgressPlugin<S, P> {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<ProgressTracker<S>>();
+            schedule_progress::<S, P>(
+                flush_schedule_mut::<S>(app),
+                self.loading.clone(),
+                self.next.clone(),
+            );
+        }
+    }
+
+    impl<S: StateMut + Clone + Eq, P: StatePattern<S> + Clone> ProgressPlugin<S, P> {
+        /// Create a [`ProgressPlugin`] from a loading state pattern and the next state to enter
+        /// once loading is complete.
+        pub fn new(loading: P, next: S) -> Self {
+            Self { loading, next }
+        }
+    }
+}
+
+use core::{
+    marker::PhantomData,
+    ops::{Add, AddAssign},
+};
+
+use bevy_diagnostic::FrameCount;
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{IntoScheduleConfigs as _, Schedule},
+    system::{In, Res, ResMut},
+};
+
+use crate::{
+    access::FlushMut,
+    pattern::StatePattern,
+    schedule::ResolveStateSystems,
+    state::{State, StateMut},
+};
+
+/// An amount of completed work out of a total amount of work.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct Progress {
+    /// The amount of completed work.
+    pub done: u32,
+    /// The total amount of work.
+    pub total: u32,
+}
+
+impl Progress {
+    /// Create a new `Progress`.
+    pub fn new(done: u32, total: u32) -> Self {
+        Self { done, total }
+    }
+
+    /// Check if all of the work is complete.
+    pub fn is_complete(self) -> bool {
+        self.done >= self.total
+    }
+
+    /// Get the fraction of completed work, from 0 to 1 (or 1 if there is no work).
+    pub fn fraction(self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        (self.done as f32 / self.total as f32).min(1.0)
+    }
+}
+
+impl From<(u32, u32)> for Progress {
+    fn from((done, total): (u32, u32)) -> Self {
+        Self::new(done, total)
+    }
+}
+
+impl From<bool> for Progress {
+    fn from(done: bool) -> Self {
+        Self::new(done as u32, 1)
+    }
+}
+
+impl Add for Progress {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.done + rhs.done, self.total + rhs.total)
+    }
+}
+
+impl AddAssign for Progress {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// A resource that collects the [`Progress`] reported while the [`State`] type `S` is loading.
+///
+/// Reported progress is summed each frame, and checked for completion in
+/// [`ResolveStateSystems::<S>::Compute`] on the following frame. If no progress is reported, the
+/// check will fail.
+///
+/// The reported progress is measured once per [`FrameCount`], even if the
+/// [`StateFlush`](crate::schedule::StateFlush) schedule runs several times per frame. Without a
+/// `FrameCount` resource (added by `FrameCountPlugin`), it's measured on every run instead.
+///
+/// Added by [`ProgressPlugin<S, P>`].
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct ProgressTracker<S: State> {
+    reported: Option<Progress>,
+    last: Option<Progress>,
+    last_frame: Option<u32>,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    _phantom: PhantomData<S>,
+}
+
+impl<S: State> Default for ProgressTracker<S> {
+    fn default() -> Self {
+        Self {
+            reported: None,
+            last: None,
+            last_frame: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: State> ProgressTracker<S> {
+    /// Report progress for the current frame.
+    pub fn report(&mut self, progress: impl Into<Progress>) {
+        *self.reported.get_or_insert_default() += progress.into();
+    }
+
+    /// Get the total progress that was reported on the previous frame.
+    pub fn progress(&self) -> Progress {
+        self.last.unwrap_or_default()
+    }
+
+    /// Check if any progress was reported on the previous frame, and all of it was complete.
+    pub fn is_complete(&self) -> bool {
+        self.last.is_some_and(Progress::is_complete)
+    }
+
+    /// Clear all reported progress.
+    pub fn reset(&mut self) {
+        self.reported = None;
+        self.last = None;
+        self.last_frame = None;
+    }
+
+    fn finish_frame(&mut self, frame: Option<u32>) {
+        // Only measure once per frame.
+        if frame.is_some() && frame == self.last_frame {
+            return;
+        }
+        self.last_frame = frame;
+        self.last = self.reported.take();
+    }
+}
+
+/// A system that reports its input [`Progress`] to the [`ProgressTracker<S>`].
+///
+/// Pipe a system that returns `Progress` into this system to track its progress, e.g.
+/// `load_audio.pipe(report_progress::<Screen>)`.
+pub fn report_progress<S: State>(
+    In(progress): In<Progress>,
+    mut tracker: ResMut<ProgressTracker<S>>,
+) {
+    tracker.report(progress);
+}
+
+/// Add [`ProgressTracker<S>`] systems for the [`State`] type `S` to a schedule.
+///
+/// While `S` is in a state matching `loading`, the next state will be set to `next` and a flush
+/// will be triggered in [`ResolveStateSystems::<S>::Compute`] once the reported progress is
+/// complete. This is skipped while the next state has already been changed or triggered by
+/// something else.
+///
+/// Used in [`ProgressPlugin<S, P>`].
+pub fn schedule_progress<S: StateMut + Clone + Eq, P: StatePattern<S>>(
+    schedule: &mut Schedule,
+    loading: P,
+    next: S,
+) {
+    schedule.add_systems(
+        (move |frame: Option<Res<FrameCount>>,
+               mut state: FlushMut<S>,
+               mut tracker: ResMut<ProgressTracker<S>>| {
+            if !state.current.is_in(&loading) {
+                tracker.reset();
+                return;
+            }
+
+            tracker.finish_frame(frame.map(|x| x.0));
+            if tracker.is_complete()
+                && !state.next.is_triggered()
+                && state.current.get() == state.next.get()
+            {
+                state.trigger().enter(next.clone());
+            }
+        })
+        .in_set(ResolveStateSystems::<S>::Compute),
+    );
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::{App, Update};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(progress(Loading => Gameplay))]
+    enum Screen {
+        #[default]
+        Loading,
+        Title,
+        Gameplay,
+    }
+
+    #[derive(Resource, Default)]
+    struct Report(Option<Progress>);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Report>()
+            .init_state::<Screen>()
+            .add_systems(
+                Update,
+                |report: Res<Report>, mut tracker: ResMut<ProgressTracker<Screen>>| {
+                    if let Some(progress) = report.0 {
+                        tracker.report(progress);
+                    }
+                },
+            );
+        app.update();
+        app
+    }
+
+    #[test]
+    fn no_reports_is_not_complete() {
+        let mut app = app();
+        app.update();
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Loading));
+    }
+
+    #[test]
+    fn complete_progress_enters_next_state() {
+        let mut app = app();
+        app.world_mut().resource_mut::<Report>().0 = Some((1, 2).into());
+        app.update();
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Loading));
+
+        app.world_mut().resource_mut::<Report>().0 = Some((2, 2).into());
+        app.update();
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+    }
+
+    #[test]
+    fn complete_progress_does_not_overwrite_next_state() {
+        let mut app = app();
+        app.world_mut().resource_mut::<Report>().0 = Some(true.into());
+        app.update();
+        app.world_mut().set_next_state(Some(Screen::Title));
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Tick(usize);
+
+    #[test]
+    fn progress_is_measured_once_per_frame_with_cascade() {
+        let mut app = app();
+        app.add_plugins((
+            StateFlushPlugin::default().cascade(4),
+            bevy_diagnostic::FrameCountPlugin,
+        ))
+        .init_state::<Tick>()
+        .add_systems(
+            StateFlush,
+            // Flush `Tick` again after an odd tick, so `StateFlush` runs twice per frame.
+            (|current: CurrentRef<Tick>, mut next: NextMut<Tick>| {
+                let tick = current.unwrap().0;
+                if tick % 2 == 1 {
+                    next.enter(Tick(tick + 1));
+                }
+            })
+            .in_set(ResolveStateSystems::<Tick>::Applied),
+        );
+        app.world_mut().resource_mut::<Report>().0 = Some((1, 2).into());
+
+        for tick in [1, 3] {
+            app.world_mut().set_next_state(Some(Tick(tick)));
+            app.update();
+            assert_eq!(app.world().state::<Tick>(), Some(&Tick(tick + 1)));
+        }
+        let tracker = app.world().resource::<ProgressTracker<Screen>>();
+        assert_eq!(tracker.progress(), Progress::new(1, 2));
+        assert!(!tracker.is_complete());
+
+        app.world_mut().resource_mut::<Report>().0 = Some((2, 2).into());
+        for tick in [5, 7] {
+            app.world_mut().set_next_state(Some(Tick(tick)));
+            app.update();
+        }
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+    }
+
+    const FIRST_LEVEL: u8 = 1;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(progress(Loading => Level(FIRST_LEVEL + 1)))]
+    enum Stage {
+        #[default]
+        Loading,
+        Level(u8),
+    }
+
+    #[test]
+    fn next_state_can_be_an_expression() {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin).init_state::<Stage>();
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<ProgressTracker<Stage>>()
+            .report(Progress::from(true));
+        app.update();
+        assert_eq!(app.world().state::<Stage>(), Some(&Stage::Level(2)));
+    }
+}
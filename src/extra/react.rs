@@ -9,7 +9,7 @@ pub use app::*;
 mod app {
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
@@ -20,7 +20,7 @@ mod app {
 
     impl<S: State + Eq> Plugin for ReactPlugin<S> {
         fn build(&self, app: &mut App) {
-            schedule_react::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_react::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
@@ -0,0 +1,372 @@
+//! Save and load the current value of registered states with `serde`.
+//!
+//! Enable the `serde` feature flag to use this module.
+//!
+//! Register a [`State`] type with the `save` [derive macro option](pyri_state_derive::State)
+//! or [`register_saved_state`], then call [`save_states`] and [`load_states`] with any `serde`
+//! data format.
+//!
+//! Each `State` type is saved under a key that must stay the same across builds for old saves to
+//! keep loading. The `save` option uses the name of the type by default, or a custom key with
+//! `save(key = "...")`.
+//!
+//! **Only registered `State` types are saved.** Implementing `Serialize` and `Deserialize` is not
+//! enough on its own, and any unregistered `State` types will keep their values on load.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::{extra::save::{load_states, save_states}, prelude::*};
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq, Serialize, Deserialize)]
+//! #[state(save(key = "screen"))]
+//! enum Screen {
+//!     Title,
+//!     Gameplay,
+//! }
+//!
+//! fn save<Ser: serde::Serializer>(world: &World, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+//!     save_states(world, serializer)
+//! }
+//!
+//! fn load<'de, D: serde::Deserializer<'de>>(world: &mut World, deserializer: D) -> Result<(), D::Error> {
+//!     // This will trigger a flush, so `Screen` hooks will run in the next `StateFlush`.
+//!     load_states(world, deserializer)
+//! }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use super::*;
+
+    /// A plugin that registers the [`State`] type `S` to be saved by [`save_states`] and
+    /// loaded by [`load_states`].
+    ///
+    /// Added by the `save` [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`register_saved_state<S>`].
+    pub struct SaveStatePlugin<S: StateMut + Serialize + DeserializeOwned> {
+        key: &'static str,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: StateMut + Serialize + DeserializeOwned> Plugin for SaveStatePlugin<S> {
+        fn build(&self, app: &mut App) {
+            register_saved_state::<S>(app.world_mut(), self.key);
+        }
+    }
+
+    impl<S: StateMut + Serialize + DeserializeOwned> SaveStatePlugin<S> {
+        /// Create a [`SaveStatePlugin`] that saves `S` under a stable key.
+        pub fn new(key: &'static str) -> Self {
+            Self {
+                key,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    any::{TypeId, type_name},
+    fmt,
+};
+
+use bevy_ecs::{resource::Resource, world::World};
+use serde::{
+    Deserializer, Serialize, Serializer,
+    de::{DeserializeOwned, DeserializeSeed, Error as _, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeMap as _,
+};
+
+use crate::{
+    setup::WorldExtState as _,
+    state::{State, StateMut},
+};
+
+type SaveFn = for<'w> fn(&'w World) -> Box<dyn erased_serde::Serialize + 'w>;
+type LoadFn =
+    for<'de> fn(&mut World, &mut dyn erased_serde::Deserializer<'de>) -> erased_serde::Result<()>;
+
+struct SavedStateEntry {
+    key: &'static str,
+    type_id: TypeId,
+    save: SaveFn,
+    load: LoadFn,
+}
+
+/// A resource that lists the [`State`] types saved by [`save_states`] and loaded by
+/// [`load_states`].
+///
+/// Each `State` type is keyed by the key it was registered with.
+#[derive(Resource, Default)]
+pub struct SavedStateRegistry {
+    entries: Vec<SavedStateEntry>,
+}
+
+impl SavedStateRegistry {
+    /// Iterate over the keys of the registered [`State`] types.
+    pub fn keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|entry| entry.key)
+    }
+}
+
+/// Register the [`State`] type `S` to be saved by [`save_states`] and loaded by [`load_states`]
+/// under `key`.
+///
+/// The key is written to the save data, so it should not change between builds.
+///
+/// Used in [`SaveStatePlugin<S>`].
+///
+/// # Panics
+///
+/// Panics if `key` is already registered for a different `State` type.
+pub fn register_saved_state<S: StateMut + Serialize + DeserializeOwned>(
+    world: &mut World,
+    key: &'static str,
+) {
+    let mut registry = world.get_resource_or_init::<SavedStateRegistry>();
+    let type_id = TypeId::of::<S>();
+    if let Some(entry) = registry.entries.iter().find(|entry| entry.key == key) {
+        assert!(
+            entry.type_id == type_id,
+            "saved state key {key:?} is already registered for a different type than {}",
+            type_name::<S>(),
+        );
+        return;
+    }
+
+    registry.entries.push(SavedStateEntry {
+        key,
+        type_id,
+        save: save_state::<S>,
+        load: load_state::<S>,
+    });
+}
+
+fn save_state<S: State + Serialize>(world: &World) -> Box<dyn erased_serde::Serialize + '_> {
+    Box::new(world.get_resource::<S>())
+}
+
+fn load_state<S: StateMut + DeserializeOwned>(
+    world: &mut World,
+    deserializer: &mut dyn erased_serde::Deserializer,
+) -> erased_serde::Result<()> {
+    let state = erased_serde::deserialize::<Option<S>>(deserializer)?;
+    world.set_next_state(state);
+    world.trigger_state::<S>();
+    Ok(())
+}
+
+/// Save the current value of every registered [`State`] type as a map, with `None` for
+/// disabled states.
+///
+/// Only registered `State` types are saved. See [`SavedStateRegistry`] for the list of
+/// registered `State` types.
+pub fn save_states<Ser: Serializer>(world: &World, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    let entries = world
+        .get_resource::<SavedStateRegistry>()
+        .map_or(&[][..], |registry| &registry.entries);
+
+    let mut map = serializer.serialize_map(Some(entries.len()))?;
+    for entry in entries {
+        map.serialize_entry(entry.key, &*(entry.save)(world))?;
+    }
+    map.end()
+}
+
+/// Set the next state of every registered [`State`] type to its value in a map written by
+/// [`save_states`], and trigger a flush for each loaded `State` type.
+///
+/// A saved `None` will disable the `State` type. Unregistered `State` types in the map will be
+/// ignored.
+pub fn load_states<'de, D: Deserializer<'de>>(
+    world: &mut World,
+    deserializer: D,
+) -> Result<(), D::Error> {
+    deserializer.deserialize_map(LoadStatesVisitor(world))
+}
+
+struct LoadStatesVisitor<'w>(&'w mut World);
+
+impl<'de> Visitor<'de> for LoadStatesVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of saved states")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            let load = self
+                .0
+                .get_resource::<SavedStateRegistry>()
+                .and_then(|registry| registry.entries.iter().find(|entry| entry.key == key))
+                .map(|entry| entry.load);
+
+            if let Some(load) = load {
+                map.next_value_seed(LoadStateSeed(self.0, load))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct LoadStateSeed<'w>(&'w mut World, LoadFn);
+
+impl<'de> DeserializeSeed<'de> for LoadStateSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.1)(self.0, &mut deserializer).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(all(test, feature = "bevy_app", feature = "stack"))]
+mod tests {
+    use alloc::{format, string::String, vec, vec::Vec};
+
+    use bevy_app::App;
+    use bevy_ecs::system::{In, ResMut};
+    use serde::Deserialize;
+
+    use crate::{next_state::stack::NextStateStack, prelude::*};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+    #[state(save)]
+    enum Screen {
+        #[default]
+        Title,
+        Gameplay,
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[state(save(key = "level"))]
+    struct Level(usize);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    #[state(save, next(NextStateStack<Self>))]
+    enum Menu {
+        Main,
+        Settings,
+        Credits,
+    }
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<String>);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Log>()
+            .init_state::<Screen>()
+            .add_state::<Level>()
+            .register_state::<Menu>()
+            .insert_state(NextStateStack::with_base(Menu::Main))
+            .add_systems(
+                StateFlush,
+                Screen::ANY.on_enter(|In(screen): In<Screen>, mut log: ResMut<Log>| {
+                    log.0.push(format!("enter {screen:?}"));
+                }),
+            );
+        app.update();
+        app
+    }
+
+    fn save(app: &App) -> String {
+        let mut output = String::new();
+        let mut serializer = ron::Serializer::new(&mut output, None).unwrap();
+        save_states(app.world(), &mut serializer).unwrap();
+        output
+    }
+
+    fn load(app: &mut App, input: &str) {
+        let mut deserializer = ron::Deserializer::from_str(input).unwrap();
+        load_states(app.world_mut(), &mut deserializer).unwrap();
+    }
+
+    fn log(app: &mut App) -> Vec<String> {
+        core::mem::take(&mut app.world_mut().resource_mut::<Log>().0)
+    }
+
+    fn stack(app: &mut App) -> &mut NextStateStack<Menu> {
+        app.world_mut()
+            .resource_mut::<NextStateStack<Menu>>()
+            .into_inner()
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut app = app();
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        stack(&mut app).push(Menu::Settings);
+        app.world_mut().trigger_state::<Menu>();
+        app.update();
+        let saved = save(&app);
+        assert_eq!(
+            app.world()
+                .resource::<SavedStateRegistry>()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["Screen", "level", "Menu"],
+        );
+
+        app.world_mut().set_next_state(Some(Screen::Title));
+        app.world_mut().set_next_state(Some(Level(3)));
+        stack(&mut app).push(Menu::Credits);
+        app.world_mut().trigger_state::<Menu>();
+        app.update();
+        log(&mut app);
+
+        load(&mut app, &saved);
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+        assert_eq!(app.world().state::<Level>(), None);
+        assert_eq!(app.world().state::<Menu>(), Some(&Menu::Settings));
+        assert_eq!(stack(&mut app).base(), 1);
+        assert_eq!(log(&mut app), ["enter Gameplay"]);
+
+        // Loading the current values still triggers a flush, so hooks run again.
+        load(&mut app, &saved);
+        app.update();
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+        assert_eq!(log(&mut app), ["enter Gameplay"]);
+
+        // The base state is kept after a load.
+        stack(&mut app).clear();
+        app.world_mut().trigger_state::<Menu>();
+        app.update();
+        assert_eq!(app.world().state::<Menu>(), Some(&Menu::Main));
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        let mut app = app();
+        load(&mut app, r#"{"Missing": Some(1), "level": Some(Level(2))}"#);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(2)));
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+    }
+
+    #[test]
+    #[should_panic]
+    fn duplicate_key_panics() {
+        let mut world = World::new();
+        register_saved_state::<Screen>(&mut world, "key");
+        register_saved_state::<Level>(&mut world, "key");
+    }
+}
@@ -0,0 +1,198 @@
+//! Enable and disable a state based on a pattern of its parent state.
+//!
+//! Enable the `substate` feature flag to use this module.
+//!
+//! Use the `substate_of(...)` [derive macro option](pyri_state_derive::State) to configure a
+//! substate:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq, Default)]
+//! enum Screen {
+//!     #[default]
+//!     Title,
+//!     Gameplay,
+//! }
+//!
+//! // Enabled with its default value in `Screen::Gameplay`, and disabled otherwise.
+//! #[derive(State, Resource, Clone, PartialEq, Eq, Default)]
+//! #[state(substate_of(Screen::Gameplay))]
+//! struct Paused(bool);
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::any::type_name;
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::{flush_schedule_label, flush_schedule_mut};
+
+    use super::*;
+
+    /// A plugin that enables the [`State`] type `S` when its parent `State` type `T` enters
+    /// a state matching `P`, and disables `S` when `T` exits a matching state.
+    ///
+    /// Added by the `substate_of(...)` [derive macro option](pyri_state_derive::State).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `S` does not flush in the same schedule as `T`.
+    ///
+    /// Calls [`schedule_substate<S, T, P>`].
+    pub struct SubstatePlugin<S, T, P>
+    where
+        S: StateMut + FromWorld,
+        T: State,
+        P: StatePattern<T> + Clone,
+    {
+        parent: P,
+        _phantom: PhantomData<(S, T)>,
+    }
+
+    impl<S, T, P> Plugin for SubstatePlugin<S, T, P>
+    where
+        S: StateMut + FromWorld,
+        T: State,
+        P: StatePattern<T> + Clone,
+    {
+        fn build(&self, app: &mut App) {
+            let label = flush_schedule_label::<S>(app.world());
+            assert_eq!(
+                flush_schedule_label::<T>(app.world()),
+                label,
+                "{} must flush in the same schedule as its parent state {}",
+                type_name::<S>(),
+                type_name::<T>(),
+            );
+
+            schedule_substate::<S, T, P>(flush_schedule_mut::<S>(app), self.parent.clone());
+        }
+    }
+
+    impl<S, T, P> SubstatePlugin<S, T, P>
+    where
+        S: StateMut + FromWorld,
+        T: State,
+        P: StatePattern<T> + Clone,
+    {
+        /// Create a [`SubstatePlugin`] from a pattern of the parent state.
+        pub fn new(parent: P) -> Self {
+            Self {
+                parent,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use core::marker::PhantomData;
+
+use bevy_ecs::{
+    schedule::{IntoScheduleConfigs as _, Schedule},
+    system::SystemState,
+    world::{FromWorld, World},
+};
+use tiny_bail::prelude::*;
+
+use crate::{
+    access::NextMut,
+    pattern::{StatePattern, StatePatternExtClone as _},
+    schedule::ResolveStateSystems,
+    state::{State, StateMut},
+};
+
+fn enter_from_world<S: StateMut + FromWorld>(
+    world: &mut World,
+    next: &mut SystemState<NextMut<S>>,
+) {
+    let value = S::from_world(world);
+    r!(next.get_mut(world)).enter(value);
+}
+
+/// Configure the [`State`] type `S` as a substate of the `State` type `T` in a schedule.
+///
+/// `S` will be resolved after `T`, enabled with its [`FromWorld`] value when `T` enters a state
+/// matching `parent`, and disabled when `T` exits a matching state.
+///
+/// Used in [`SubstatePlugin<S, T, P>`].
+pub fn schedule_substate<S, T, P>(schedule: &mut Schedule, parent: P)
+where
+    S: StateMut + FromWorld,
+    T: State,
+    P: StatePattern<T> + Clone,
+{
+    schedule
+        .configure_sets(ResolveStateSystems::<S>::Resolve.after(ResolveStateSystems::<T>::Resolve));
+    schedule.add_systems(parent.on_edge(S::disable, enter_from_world::<S>));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::{resource::Resource, schedule::ScheduleLabel};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    enum Screen {
+        #[default]
+        Title,
+        Gameplay,
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(substate_of(Screen::Gameplay))]
+    struct Paused(bool);
+
+    #[test]
+    fn substate_follows_parent_pattern() {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_state::<Screen>()
+            .add_state::<Paused>();
+        app.update();
+        assert_eq!(app.world().state::<Paused>(), None);
+
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert_eq!(app.world().state::<Paused>(), Some(&Paused(false)));
+
+        app.world_mut().set_next_state(Some(Paused(true)));
+        app.update();
+        assert_eq!(app.world().state::<Paused>(), Some(&Paused(true)));
+
+        // Leaving the pattern disables the substate.
+        app.world_mut().set_next_state(Some(Screen::Title));
+        app.update();
+        assert_eq!(app.world().state::<Paused>(), None);
+
+        // Matching again re-enters the substate with its `FromWorld` value.
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert_eq!(app.world().state::<Paused>(), Some(&Paused(false)));
+    }
+
+    #[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
+    struct Custom;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(flush_in(Custom), substate_of(Screen::Gameplay))]
+    struct Remote;
+
+    #[test]
+    #[should_panic(expected = "must flush in the same schedule as its parent state")]
+    fn parent_in_another_schedule_panics() {
+        App::new()
+            .add_plugins(StatePlugin)
+            .init_state::<Screen>()
+            .add_state::<Remote>();
+    }
+}
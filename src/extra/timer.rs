@@ -0,0 +1,410 @@
+//! Track how long a state has been in its current value with a [`StateTimer`], and schedule
+//! delayed state transitions.
+//!
+//! Enable the `timer` feature flag to use this module.
+//!
+//! Use the `timer` [derive macro option](pyri_state_derive::State) to add a `StateTimer`, then
+//! use the [`StatePatternExtTimer`] run conditions:
+//!
+//! ```
+//! # use core::time::Duration;
+//! #
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! #[state(timer)]
+//! enum Screen {
+//!     Splash,
+//!     Title,
+//! }
+//!
+//! # fn skip_splash() {}
+//! # fn show_idle_hint() {}
+//! #
+//! # fn plugin(app: &mut App) {
+//! app.add_systems(Update, (
+//!     skip_splash.run_if(Screen::Splash.in_state_for(Duration::from_secs(3))),
+//!     show_idle_hint.run_if(Screen::Title.after_secs_real(30.0)),
+//! ));
+//! # }
+//! ```
+//!
+//! Use [`StatePatternExtTimer::after`] to enter a new state after a delay:
+//!
+//! ```
+//! # use core::time::Duration;
+//! #
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! # #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! # #[state(timer)]
+//! # enum Screen {
+//! #     Splash,
+//! #     Title,
+//! # }
+//! #
+//! # fn plugin(app: &mut App) {
+//! app.add_systems(
+//!     StateFlush,
+//!     Screen::Splash.after(Duration::from_secs(3)).enter(Screen::Title),
+//! );
+//! # }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds a [`StateTimer<S>`] resource and a reset system for the [`State`]
+    /// type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `timer` [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`schedule_timer<S>`].
+    pub struct StateTimerPlugin<S: State>(PhantomData<S>);
+
+    impl<S: State> Plugin for StateTimerPlugin<S> {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<StateTimer<S>>();
+            schedule_timer::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State> Default for StateTimerPlugin<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+}
+
+use core::{marker::PhantomData, time::Duration};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::{
+    change_detection::DetectChanges as _,
+    resource::Resource,
+    schedule::{IntoScheduleConfigs as _, Schedule, ScheduleConfigs},
+    system::{Local, Res, ResMut, ScheduleSystem},
+};
+use bevy_time::{Fixed, Real, Time, Virtual};
+
+use crate::{
+    access::{CurrentRef, FlushMut},
+    pattern::StatePattern,
+    schedule::ResolveStateSystems,
+    state::{State, StateMut},
+};
+
+/// A resource that stores when the [`State`] type `S` last applied a flush.
+///
+/// Added by [`StateTimerPlugin<S>`].
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct StateTimer<S: State> {
+    virtual_start: Duration,
+    real_start: Duration,
+    fixed_start: Duration,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    _phantom: PhantomData<S>,
+}
+
+impl<S: State> Default for StateTimer<S> {
+    fn default() -> Self {
+        Self {
+            virtual_start: Duration::ZERO,
+            real_start: Duration::ZERO,
+            fixed_start: Duration::ZERO,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: State> StateTimer<S> {
+    /// Get the elapsed [`Time<Virtual>`] since the last flush.
+    pub fn elapsed(&self, time: &Time<Virtual>) -> Duration {
+        time.elapsed().saturating_sub(self.virtual_start)
+    }
+
+    /// Get the elapsed [`Time<Real>`] since the last flush.
+    pub fn elapsed_real(&self, time: &Time<Real>) -> Duration {
+        time.elapsed().saturating_sub(self.real_start)
+    }
+
+    /// Get the elapsed [`Time<Fixed>`] since the last flush.
+    pub fn elapsed_fixed(&self, time: &Time<Fixed>) -> Duration {
+        time.elapsed().saturating_sub(self.fixed_start)
+    }
+
+    /// Reset the timer to the current [`Time<Virtual>`], [`Time<Real>`], and [`Time<Fixed>`].
+    pub fn reset(
+        &mut self,
+        virtual_time: &Time<Virtual>,
+        real_time: &Time<Real>,
+        fixed_time: &Time<Fixed>,
+    ) {
+        self.virtual_start = virtual_time.elapsed();
+        self.real_start = real_time.elapsed();
+        self.fixed_start = fixed_time.elapsed();
+    }
+}
+
+/// A clock that a [`StateTimer`] can measure elapsed time with.
+///
+/// Implemented for [`Virtual`], [`Real`], and [`Fixed`].
+pub trait StateTimerClock: 'static + Send + Sync + Default {
+    /// Get the elapsed time of this clock since the last flush.
+    fn elapsed<S: State>(timer: &StateTimer<S>, time: &Time<Self>) -> Duration;
+}
+
+impl StateTimerClock for Virtual {
+    fn elapsed<S: State>(timer: &StateTimer<S>, time: &Time<Self>) -> Duration {
+        timer.elapsed(time)
+    }
+}
+
+impl StateTimerClock for Real {
+    fn elapsed<S: State>(timer: &StateTimer<S>, time: &Time<Self>) -> Duration {
+        timer.elapsed_real(time)
+    }
+}
+
+impl StateTimerClock for Fixed {
+    fn elapsed<S: State>(timer: &StateTimer<S>, time: &Time<Self>) -> Duration {
+        timer.elapsed_fixed(time)
+    }
+}
+
+/// A delay after the [`State`] type `S` enters a state matching `P`, measured with the
+/// [`StateTimerClock`] `T`.
+///
+/// The usual way to construct this type is with [`StatePatternExtTimer::after`],
+/// [`after_real`](StatePatternExtTimer::after_real), or
+/// [`after_fixed`](StatePatternExtTimer::after_fixed).
+pub struct StateDelay<S: State, P: StatePattern<S>, T: StateTimerClock> {
+    pattern: P,
+    duration: Duration,
+    _phantom: PhantomData<(S, T)>,
+}
+
+impl<S: State, P: StatePattern<S>, T: StateTimerClock> StateDelay<S, P, T> {
+    /// Create a new `StateDelay`.
+    pub fn new(pattern: P, duration: Duration) -> Self {
+        Self {
+            pattern,
+            duration,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Configure a system to set the next state to `value` and trigger a flush in
+    /// [`ResolveStateSystems::<S>::Compute`] once `S` has been in a matching state for the delay.
+    ///
+    /// The transition is cancelled if `S` flushes before the delay elapses. It fires at most once
+    /// per visit to the current state, even if the resulting flush is rejected, and it's skipped
+    /// while the next state has already been changed or triggered by something else.
+    pub fn enter(self, value: S) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: StateMut + Clone + Eq,
+    {
+        let Self {
+            pattern, duration, ..
+        } = self;
+
+        (move |mut state: FlushMut<S>,
+               timer: Res<StateTimer<S>>,
+               time: Res<Time<T>>,
+               mut fired: Local<bool>| {
+            if timer.is_changed() {
+                *fired = false;
+            }
+            if *fired
+                || !state.current.is_in(&pattern)
+                || T::elapsed(&timer, &time) < duration
+                || state.next.is_triggered()
+                || state.current.get() != state.next.get()
+            {
+                return;
+            }
+
+            state.trigger().enter(value.clone());
+            *fired = true;
+        })
+        .in_set(ResolveStateSystems::<S>::Compute)
+    }
+}
+
+/// An extension trait for [`StatePattern<S>`] that provides [`StateTimer<S>`] run conditions and
+/// delays.
+pub trait StatePatternExtTimer<S: State>: StatePattern<S> {
+    /// Build a run condition that checks if `S` has been in a matching state for at least
+    /// `duration` of [`Time<Virtual>`].
+    fn in_state_for(
+        self,
+        duration: Duration,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<StateTimer<S>>, Res<Time<Virtual>>) -> bool
+    {
+        move |state, timer, time| state.is_in(&self) && timer.elapsed(&time) >= duration
+    }
+
+    /// Build a run condition that checks if `S` has been in a matching state for at least
+    /// `duration` of [`Time<Real>`].
+    fn in_state_for_real(
+        self,
+        duration: Duration,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<StateTimer<S>>, Res<Time<Real>>) -> bool
+    {
+        move |state, timer, time| state.is_in(&self) && timer.elapsed_real(&time) >= duration
+    }
+
+    /// Build a run condition that checks if `S` has been in a matching state for at least
+    /// `secs` seconds of [`Time<Virtual>`].
+    fn after_secs(
+        self,
+        secs: f32,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<StateTimer<S>>, Res<Time<Virtual>>) -> bool
+    {
+        self.in_state_for(Duration::from_secs_f32(secs))
+    }
+
+    /// Build a run condition that checks if `S` has been in a matching state for at least
+    /// `secs` seconds of [`Time<Real>`].
+    fn after_secs_real(
+        self,
+        secs: f32,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<StateTimer<S>>, Res<Time<Real>>) -> bool
+    {
+        self.in_state_for_real(Duration::from_secs_f32(secs))
+    }
+
+    /// Build a [`StateDelay`] that elapses after `S` has been in a matching state for
+    /// `duration` of [`Time<Virtual>`].
+    fn after(self, duration: Duration) -> StateDelay<S, Self, Virtual> {
+        StateDelay::new(self, duration)
+    }
+
+    /// Build a [`StateDelay`] that elapses after `S` has been in a matching state for
+    /// `duration` of [`Time<Real>`].
+    fn after_real(self, duration: Duration) -> StateDelay<S, Self, Real> {
+        StateDelay::new(self, duration)
+    }
+
+    /// Build a [`StateDelay`] that elapses after `S` has been in a matching state for
+    /// `duration` of [`Time<Fixed>`].
+    ///
+    /// This is deterministic with respect to the number of fixed timesteps that have run.
+    ///
+    /// Note that the delay is still checked in the [`StateFlush`](crate::schedule::StateFlush)
+    /// schedule, which reads [`Time<Fixed>`] outside of `FixedUpdate`. If the delay elapses during
+    /// a fixed timestep, the transition happens at the next flush rather than within that timestep.
+    fn after_fixed(self, duration: Duration) -> StateDelay<S, Self, Fixed> {
+        StateDelay::new(self, duration)
+    }
+}
+
+impl<S: State, P: StatePattern<S>> StatePatternExtTimer<S> for P {}
+
+fn reset_timer<S: State>(
+    mut timer: ResMut<StateTimer<S>>,
+    virtual_time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    timer.reset(&virtual_time, &real_time, &fixed_time);
+}
+
+/// Add a [`StateTimer<S>`] reset system for the [`State`] type `S` to a schedule.
+///
+/// Used in [`StateTimerPlugin<S>`].
+pub fn schedule_timer<S: State>(schedule: &mut Schedule) {
+    schedule.add_systems(reset_timer::<S>.in_set(ResolveStateSystems::<S>::AnyFlush));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(guard_flush, timer)]
+    enum Screen {
+        #[default]
+        Splash,
+        Title,
+        Credits,
+    }
+
+    #[derive(Resource, Default)]
+    struct Allow(bool);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Time<Virtual>>()
+            .init_resource::<Time<Real>>()
+            .init_resource::<Time<Fixed>>()
+            .init_resource::<Allow>()
+            .init_state::<Screen>()
+            .add_systems(
+                StateFlush,
+                (
+                    Screen::Splash
+                        .after(Duration::from_secs(1))
+                        .enter(Screen::Title),
+                    Screen::ANY_TO_ANY.guard(|allow: Res<Allow>| allow.0),
+                ),
+            );
+        app.world_mut().resource_mut::<Allow>().0 = true;
+        app.update();
+        app.world_mut().resource_mut::<Allow>().0 = false;
+        app
+    }
+
+    fn advance(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    #[test]
+    fn delay_fires_once() {
+        let mut app = app();
+        advance(&mut app, 1.0);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Splash));
+
+        // The rejected flush isn't retried once the guard allows it.
+        app.world_mut().resource_mut::<Allow>().0 = true;
+        advance(&mut app, 1.0);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Splash));
+    }
+
+    #[test]
+    fn delay_does_not_overwrite_next_state() {
+        let mut app = app();
+        app.world_mut().resource_mut::<Allow>().0 = true;
+        app.world_mut().set_next_state(Some(Screen::Credits));
+        advance(&mut app, 1.0);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Credits));
+    }
+}
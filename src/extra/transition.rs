@@ -0,0 +1,438 @@
+//! Animate state transitions in two phases with a [`TransitionProgress`] resource.
+//!
+//! Enable the `transition` feature flag to use this module.
+//!
+//! When the [`State`] type `S` is triggered to flush, the flush will be held for an exit
+//! duration while `S` is [exiting](TransitionPhase::Exiting), then applied, then followed by an
+//! enter duration while `S` is [entering](TransitionPhase::Entering). Setting the next state back
+//! to the current state while exiting will cancel the transition.
+//!
+//! Transitions advance with [`Time<Virtual>`] once per frame, even if the
+//! [`StateFlush`](crate::schedule::StateFlush) schedule runs several times per frame.
+//!
+//! Use the `transition(exit = ..., enter = ...)` [derive macro option](pyri_state_derive::State)
+//! to configure the durations in seconds, then use the [`StatePatternExtTransition`] methods:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use pyri_state::prelude::*;
+//! #
+//! #[derive(State, Resource, Clone, PartialEq, Eq)]
+//! #[state(transition(exit = 0.3, enter = 0.3))]
+//! enum Screen {
+//!     Title,
+//!     Gameplay,
+//! }
+//!
+//! fn fade_out(progress: Res<TransitionProgress<Screen>>) {
+//!     let _alpha = progress.fraction();
+//!     // ...
+//! }
+//!
+//! fn fade_in(progress: Res<TransitionProgress<Screen>>) {
+//!     let _alpha = 1.0 - progress.fraction();
+//!     // ...
+//! }
+//!
+//! # fn plugin(app: &mut App) {
+//! app.add_systems(Update, (
+//!     Screen::ANY.on_exiting(fade_out),
+//!     Screen::ANY.on_entering(fade_in),
+//! ));
+//! # }
+//! ```
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds a [`TransitionProgress<S>`] resource and transition systems for the
+    /// [`State`] type `S` to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `transition(exit = ..., enter = ...)`
+    /// [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`schedule_transition<S>`].
+    pub struct TransitionPlugin<S: State + Eq> {
+        exit: Duration,
+        enter: Duration,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: State + Eq> Plugin for TransitionPlugin<S> {
+        fn build(&self, app: &mut App) {
+            app.insert_resource(TransitionProgress::<S>::new(self.exit, self.enter));
+            schedule_transition::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State + Eq> TransitionPlugin<S> {
+        /// Create a [`TransitionPlugin`] with an exit duration and an enter duration.
+        pub fn new(exit: Duration, enter: Duration) -> Self {
+            Self {
+                exit,
+                enter,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use core::{marker::PhantomData, time::Duration};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{IntoScheduleConfigs, Schedule, ScheduleConfigs},
+    system::{ParamSet, Res, ResMut, ScheduleSystem},
+};
+use bevy_time::{Time, Virtual};
+
+use crate::{
+    access::{CurrentRef, FlushRef, NextRef},
+    next_state::TriggerStateFlush,
+    pattern::StatePattern,
+    schedule::ResolveStateSystems,
+    state::State,
+};
+
+/// The phase of a two-phase transition, stored in [`TransitionProgress`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum TransitionPhase {
+    /// No transition is in progress.
+    #[default]
+    Idle,
+    /// The flush is being held while the old state exits.
+    Exiting,
+    /// The flush has been applied and the new state is entering.
+    Entering,
+}
+
+/// A resource that tracks the progress of a two-phase transition of the [`State`] type `S`.
+///
+/// Added by [`TransitionPlugin<S>`].
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct TransitionProgress<S: State> {
+    phase: TransitionPhase,
+    elapsed: Duration,
+    exit: Duration,
+    enter: Duration,
+    ready: bool,
+    refresh: bool,
+    last_update: Duration,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    _phantom: PhantomData<S>,
+}
+
+impl<S: State> TransitionProgress<S> {
+    /// Create a new idle `TransitionProgress` with an exit duration and an enter duration.
+    pub fn new(exit: Duration, enter: Duration) -> Self {
+        Self {
+            phase: TransitionPhase::Idle,
+            elapsed: Duration::ZERO,
+            exit,
+            enter,
+            ready: false,
+            refresh: false,
+            last_update: Duration::ZERO,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the current [`TransitionPhase`].
+    pub fn phase(&self) -> TransitionPhase {
+        self.phase
+    }
+
+    /// Check if the old state is exiting.
+    pub fn is_exiting(&self) -> bool {
+        self.phase == TransitionPhase::Exiting
+    }
+
+    /// Check if the new state is entering.
+    pub fn is_entering(&self) -> bool {
+        self.phase == TransitionPhase::Entering
+    }
+
+    /// Get the progress through the current phase, from 0 to 1 (or 0 if idle).
+    pub fn fraction(&self) -> f32 {
+        let duration = match self.phase {
+            TransitionPhase::Idle => return 0.0,
+            TransitionPhase::Exiting => self.exit,
+            TransitionPhase::Entering => self.enter,
+        };
+        if duration.is_zero() {
+            return 1.0;
+        }
+
+        (self.elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Get the elapsed time in the current phase.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the duration of the exit phase.
+    pub fn exit_duration(&self) -> Duration {
+        self.exit
+    }
+
+    /// Get the duration of the enter phase.
+    pub fn enter_duration(&self) -> Duration {
+        self.enter
+    }
+}
+
+/// An extension trait for [`StatePattern<S>`] that provides [`TransitionProgress<S>`] run
+/// conditions.
+pub trait StatePatternExtTransition<S: State>: StatePattern<S> {
+    /// Build a run condition that checks if `S` is exiting a matching state.
+    #[allow(clippy::wrong_self_convention)]
+    fn is_exiting(
+        self,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<TransitionProgress<S>>) -> bool {
+        move |state, progress| progress.is_exiting() && state.is_in(&self)
+    }
+
+    /// Configure systems to run while `S` is exiting a matching state.
+    fn on_exiting<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems.run_if(self.is_exiting())
+    }
+
+    /// Build a run condition that checks if `S` is entering a matching state.
+    #[allow(clippy::wrong_self_convention)]
+    fn is_entering(
+        self,
+    ) -> impl 'static + Send + Sync + Fn(CurrentRef<S>, Res<TransitionProgress<S>>) -> bool {
+        move |state, progress| progress.is_entering() && state.is_in(&self)
+    }
+
+    /// Configure systems to run while `S` is entering a matching state.
+    fn on_entering<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems.run_if(self.is_entering())
+    }
+}
+
+impl<S: State, P: StatePattern<S>> StatePatternExtTransition<S> for P {}
+
+fn advance_transition<S: State + Eq>(
+    time: Res<Time<Virtual>>,
+    mut state: ParamSet<(FlushRef<S>, ResMut<TriggerStateFlush<S>>)>,
+    mut progress: ResMut<TransitionProgress<S>>,
+) {
+    // Only advance once per frame.
+    let now = time.elapsed();
+    let delta = now.saturating_sub(progress.last_update);
+    progress.last_update = now;
+
+    // Cancel the transition if the held flush was not applied (e.g. rejected by a guard).
+    if progress.ready {
+        progress.ready = false;
+        progress.phase = TransitionPhase::Idle;
+        return;
+    }
+    if progress.phase == TransitionPhase::Idle {
+        return;
+    }
+
+    // Cancel the transition if the next state was set back to the current state.
+    if progress.phase == TransitionPhase::Exiting && !progress.refresh && !state.p0().will_change()
+    {
+        progress.phase = TransitionPhase::Idle;
+        progress.elapsed = Duration::ZERO;
+        return;
+    }
+
+    progress.elapsed += delta;
+    match progress.phase {
+        TransitionPhase::Exiting if progress.elapsed >= progress.exit => {
+            progress.ready = true;
+            state.p1().0 = true;
+        }
+        TransitionPhase::Entering if progress.elapsed >= progress.enter => {
+            progress.phase = TransitionPhase::Idle;
+            progress.elapsed = Duration::ZERO;
+        }
+        _ => {}
+    }
+}
+
+fn hold_flush<S: State + Eq>(
+    mut state: ParamSet<(FlushRef<S>, ResMut<TriggerStateFlush<S>>)>,
+    mut progress: ResMut<TransitionProgress<S>>,
+) {
+    if progress.ready || progress.exit.is_zero() || state.p0().current.is_disabled() {
+        return;
+    }
+
+    if progress.phase != TransitionPhase::Exiting {
+        progress.phase = TransitionPhase::Exiting;
+        progress.elapsed = Duration::ZERO;
+        progress.refresh = !state.p0().will_change();
+    }
+    state.p1().0 = false;
+}
+
+fn start_entering<S: State>(state: NextRef<S>, mut progress: ResMut<TransitionProgress<S>>) {
+    progress.ready = false;
+    progress.elapsed = Duration::ZERO;
+    progress.phase = if progress.enter.is_zero() || state.will_be_disabled() {
+        TransitionPhase::Idle
+    } else {
+        TransitionPhase::Entering
+    };
+}
+
+/// Add [`TransitionProgress<S>`] systems for the [`State`] type `S` to a schedule.
+///
+/// The flush is held in [`ResolveStateSystems::<S>::Hold`], after any guards have had a chance
+/// to reject it and after any [`ExitBarrier<S>`](crate::extra::barrier::ExitBarrier) has been
+/// released.
+///
+/// Used in [`TransitionPlugin<S>`].
+pub fn schedule_transition<S: State + Eq>(schedule: &mut Schedule) {
+    let hold = hold_flush::<S>
+        .run_if(S::is_triggered)
+        .in_set(ResolveStateSystems::<S>::Hold);
+    #[cfg(feature = "barrier")]
+    let hold = hold.after(crate::extra::barrier::hold_flush::<S>);
+
+    schedule.add_systems((
+        advance_transition::<S>.in_set(ResolveStateSystems::<S>::Compute),
+        hold,
+        start_entering::<S>.in_set(ResolveStateSystems::<S>::AnyFlush),
+    ));
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::resource::Resource;
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(guard_flush, transition(exit = 1.0, enter = 1.0))]
+    enum Screen {
+        #[default]
+        Title,
+        Gameplay,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Time<Virtual>>()
+            .init_state::<Screen>();
+
+        // Finish entering the initial state.
+        app.update();
+        advance(&mut app, 1.0);
+        assert_eq!(phase(&app), TransitionPhase::Idle);
+        app
+    }
+
+    fn advance(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    fn phase(app: &App) -> TransitionPhase {
+        app.world().resource::<TransitionProgress<Screen>>().phase()
+    }
+
+    #[test]
+    fn flush_is_held_while_exiting() {
+        let mut app = app();
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert_eq!(phase(&app), TransitionPhase::Exiting);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+
+        advance(&mut app, 0.5);
+        assert_eq!(phase(&app), TransitionPhase::Exiting);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+
+        advance(&mut app, 0.5);
+        assert_eq!(phase(&app), TransitionPhase::Entering);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+
+        advance(&mut app, 1.0);
+        assert_eq!(phase(&app), TransitionPhase::Idle);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Gameplay));
+    }
+
+    #[test]
+    fn extra_flushes_do_not_advance_time() {
+        let mut app = app();
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_secs_f32(0.5));
+        for _ in 0..4 {
+            app.world_mut().run_schedule(StateFlush);
+        }
+        app.update();
+
+        let progress = app.world().resource::<TransitionProgress<Screen>>();
+        assert_eq!(progress.phase(), TransitionPhase::Exiting);
+        assert_eq!(progress.elapsed(), Duration::from_secs_f32(0.5));
+    }
+
+    #[test]
+    fn resetting_next_state_cancels_exit() {
+        let mut app = app();
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert_eq!(phase(&app), TransitionPhase::Exiting);
+
+        app.world_mut().set_next_state(Some(Screen::Title));
+        advance(&mut app, 0.5);
+        assert_eq!(phase(&app), TransitionPhase::Idle);
+
+        advance(&mut app, 1.0);
+        assert_eq!(phase(&app), TransitionPhase::Idle);
+        assert!(!app.world().resource::<TriggerStateFlush<Screen>>().0);
+        assert_eq!(app.world().state::<Screen>(), Some(&Screen::Title));
+    }
+
+    #[test]
+    fn guard_rejection_prevents_exit() {
+        let mut app = app();
+        app.add_systems(StateFlush, Screen::ANY_TO_ANY.guard(|| false));
+
+        app.world_mut().set_next_state(Some(Screen::Gameplay));
+        app.update();
+        assert_eq!(phase(&app), TransitionPhase::Idle);
+        assert_eq!(app.world_mut().next_state::<Screen>(), Some(Screen::Title));
+    }
+}
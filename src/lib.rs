@@ -5,8 +5,9 @@
 //!
 //! # Overview
 //!
-//! 1. The current state is a [`Resource`](bevy_ecs::resource::Resource) or
-//!    [`Component`](bevy_ecs::component::Component) that implements [`State`](state::State).
+//! 1. The current state is a [`Resource`](bevy_ecs::resource::Resource) that implements
+//!    [`State`](state::State), or a [`Component`](bevy_ecs::component::Component) that implements
+//!    [`LocalState`](extra::local::LocalState).
 //! 2. The [next state](next_state) is stored in a
 //!    [`NextStateBuffer`](next_state::buffer::NextStateBuffer) resource by default.
 //! 3. A state flush is triggered by the [`TriggerStateFlush`](next_state::TriggerStateFlush)
@@ -128,11 +129,17 @@ pub mod prelude {
         access::{CurrentMut, CurrentRef, FlushMut, FlushRef, NextMut, NextRef},
         next_state::{buffer::NextStateBuffer, stack::NextStateStackCommandsExt as _},
         pattern::{
-            StatePattern as _, StatePatternExtClone as _, StatePatternExtEq as _,
-            StateTransPattern as _, StateTransPatternExtClone as _,
+            StateFlushPattern as _, StatePattern as _, StatePatternExtClone as _,
+            StatePatternExtEq as _, StatePatternExtOrd as _, StateTransPattern as _,
+            StateTransPatternExtClone as _, StateTransPatternExtOrd as _,
         },
-        schedule::{StateFlush, flush_message::StateFlushMessage},
-        setup::CommandsExtState as _,
+        schedule::{
+            StateFlush,
+            flush_message::StateFlushMessage,
+            guard_flush::StateFlushRejected,
+            observe_flush::{EnterState, ExitState, TransState},
+        },
+        setup::{CommandsExtState as _, WorldExtState as _},
         state,
         state::{
             State, StateExtEq as _, StateMut as _, StateMutExtClone as _, StateMutExtDefault as _,
@@ -140,7 +147,10 @@ pub mod prelude {
     };
 
     #[cfg(feature = "bevy_app")]
-    pub use crate::setup::{AppExtState as _, StatePlugin};
+    pub use crate::setup::{AppExtState as _, StateFlushPlugin, StatePlugin};
+
+    #[cfg(feature = "barrier")]
+    pub use crate::extra::barrier::ExitBarrier;
 
     #[cfg(feature = "bevy_state")]
     pub use crate::extra::bevy_state::{BevyState, StateExtBevy as _};
@@ -148,6 +158,21 @@ pub mod prelude {
     #[cfg(feature = "debug")]
     pub use crate::debug::StateDebugSettings;
 
+    #[cfg(feature = "history")]
+    pub use crate::extra::history::{StateHistory, StateMutExtHistory as _};
+
+    #[cfg(feature = "local")]
+    pub use crate::extra::local::{
+        LocalFlushMut, LocalFlushRef, LocalNextStateBuffer, LocalState, LocalStatePattern as _,
+        LocalStateTransPattern as _,
+    };
+
+    #[cfg(all(feature = "bevy_app", feature = "local"))]
+    pub use crate::extra::local::LocalStatePlugin;
+
+    #[cfg(feature = "progress")]
+    pub use crate::extra::progress::{Progress, ProgressTracker, report_progress};
+
     #[cfg(feature = "react")]
     pub use crate::extra::react::{
         DespawnOnDisableState, DespawnOnExitState, EnabledInEnabledState, EnabledInState,
@@ -167,6 +192,12 @@ pub mod prelude {
         NextStateStack, NextStateStackMut as _, NextStateStackMutExtClone as _,
     };
 
+    #[cfg(feature = "timer")]
+    pub use crate::extra::timer::{StatePatternExtTimer as _, StateTimer};
+
+    #[cfg(feature = "transition")]
+    pub use crate::extra::transition::{StatePatternExtTransition as _, TransitionProgress};
+
     /// A derive macro for the [`State`],
     /// [`RegisterState`](crate::setup::RegisterState), and
     /// [`Resource`](bevy_ecs::resource::Resource) traits.
@@ -207,7 +238,7 @@ pub mod prelude {
     /// # #[state(no_defaults)]
     /// # struct RawState;
     /// #
-    /// #[derive(State, Resource, Clone, PartialEq, Eq, Hash, Debug)]
+    /// #[derive(State, Resource, Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
     /// #[state(
     ///     // Disable default plugins: detect_change, flush_message, apply_flush.
     ///     no_defaults,
@@ -215,6 +246,12 @@ pub mod prelude {
     ///     detect_change,
     ///     // Write a message on flush (requires Clone).
     ///     flush_message,
+    ///     // Allow guards to reject a flush and write a message (requires Clone, implied by `transitions`).
+    ///     guard_flush,
+    ///     // Trigger observer events on flush (requires Clone).
+    ///     observe_flush,
+    ///     // Apply deferred commands between the exit, transition, and enter hooks.
+    ///     sync_flush,
     ///     // Log on flush (requires Debug).
     ///     log_flush,
     ///     // Include a `BevyState<Self>` wrapper (requires StateMut, Clone, PartialEq, Eq, Hash, Debug).
@@ -223,8 +260,24 @@ pub mod prelude {
     ///     react,
     ///     // Clone the next state into the current state on flush (requires Clone).
     ///     apply_flush,
+    ///     // Save and load with `save_states` and `load_states` under a stable key (requires StateMut, Serialize, Deserialize).
+    ///     save(key = "configured_state"),
+    ///     // Record the last 16 flushes in a `StateHistory<Self>` resource (requires Clone, Eq).
+    ///     history(capacity = 16),
+    ///     // Reset a `StateTimer<Self>` resource on flush.
+    ///     timer,
+    ///     // Hold each flush for 0.3 seconds of exiting, then 0.3 seconds of entering (requires Eq).
+    ///     transition(exit = 0.3, enter = 0.3),
+    ///     // Hold each flush while an `ExitBarrier<Self>` resource is held, for up to 10 seconds.
+    ///     exit_barrier(timeout = 10.0),
+    ///     // Enter the next state once a `ProgressTracker<Self>` resource is complete (requires StateMut, Clone, Eq).
+    ///     progress(ConfiguredState => ConfiguredState),
+    ///     // Block any transition not listed in this table (requires StateMut, Clone, Debug).
+    ///     transitions(ConfiguredState => ConfiguredState),
     ///     // Swap out the default `NextStateBuffer<Self>` for another `NextState` type.
     ///     next(NextStateStack<Self>),
+    ///     // Flush in a different schedule than `StateFlush` (dependent states must flush in the same schedule).
+    ///     flush_in(Last),
     ///     // Run this state's on-flush hooks after the listed states.
     ///     after(MyState),
     ///     // Run this state's on-flush hooks before the listed states.
@@ -24,6 +24,7 @@ use crate::{
     derive(bevy_reflect::Reflect),
     reflect(Resource)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NextStateBuffer<S: State>(
     /// The next state, or `None` if disabled.
     pub Option<S>,
@@ -32,10 +32,11 @@ pub mod stack;
     derive(bevy_reflect::Reflect),
     reflect(Resource)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TriggerStateFlush<S: State>(
     /// The flush flag. If true, `S` will flush in the [`StateFlush`](crate::schedule::StateFlush) schedule.
     pub bool,
-    PhantomData<S>,
+    #[cfg_attr(feature = "serde", serde(skip))] PhantomData<S>,
 );
 
 impl<S: State> Default for TriggerStateFlush<S> {
@@ -44,6 +45,45 @@ impl<S: State> Default for TriggerStateFlush<S> {
     }
 }
 
+/// A [`Resource`] that records whether the [`State`] type `S` applied a flush in the latest run
+/// of the [`StateFlush`](crate::schedule::StateFlush) schedule.
+///
+/// Used to run [`ResolveStateSystems::<S>::Applied`](crate::schedule::ResolveStateSystems::Applied).
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct AppliedStateFlush<S: State>(
+    /// The applied flag. If true, `S` applied a flush in the latest run of the
+    /// [`StateFlush`](crate::schedule::StateFlush) schedule.
+    pub bool,
+    PhantomData<S>,
+);
+
+impl<S: State> Default for AppliedStateFlush<S> {
+    fn default() -> Self {
+        Self(false, PhantomData)
+    }
+}
+
+/// A [`Resource`] that stores the value of the [`State`] type `S` from before its latest applied
+/// flush, or `None` if it was disabled.
+///
+/// Recorded in [`ResolveStateSystems::<S>::AnyFlush`](crate::schedule::ResolveStateSystems::AnyFlush)
+/// by [`StatePattern::on_exited`](crate::pattern::StatePattern::on_exited) hooks.
+#[derive(Resource, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct PreviousState<S: State>(
+    /// The previous state, or `None` if disabled.
+    pub Option<S>,
+);
+
 /// A [`Resource`] that determines the next state for [`Self::State`].
 ///
 /// Use [`NextRef`](crate::access::NextRef) or [`FlushRef`](crate::access::FlushRef)
@@ -25,6 +25,7 @@ use crate::{next_state::NextState, state::State};
     derive(bevy_reflect::Reflect),
     reflect(Resource)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NextStateSequence<S: State>(
     /// The sequence of states.
     pub Vec<Option<S>>,
@@ -47,10 +48,11 @@ impl<S: State> NextStateSequence<S> {
     derive(bevy_reflect::Reflect),
     reflect(Resource)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NextStateIndex<S: State>(
     /// The index into the sequence, or `None` if not in the sequence.
     pub Option<usize>,
-    PhantomData<S>,
+    #[cfg_attr(feature = "serde", serde(skip))] PhantomData<S>,
 );
 
 impl<S: State> NextState for NextStateIndex<S> {
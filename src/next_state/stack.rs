@@ -29,6 +29,7 @@ use crate::{
     derive(bevy_reflect::Reflect),
     reflect(Resource)
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NextStateStack<S: State<Next = Self>> {
     stack: Vec<Option<S>>,
     bases: Vec<usize>,
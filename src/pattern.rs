@@ -3,17 +3,32 @@
 //! Use the [`state!`](crate::state!) macro to build [`StatePattern`] and
 //! [`StateTransPattern`] instances.
 
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    ops::{BitAnd, BitOr, Bound, Not, RangeBounds as _},
+};
 
 use bevy_ecs::{
+    bundle::Bundle,
+    error::Result,
+    observer::Observer,
     schedule::{IntoScheduleConfigs, ScheduleConfigs, SystemCondition},
-    system::ScheduleSystem,
+    system::{
+        Commands, In, InRef, IntoObserverSystem, IntoSystem, Local, ReadOnlySystemParam,
+        RegisteredSystemError, Res, ScheduleSystem, StaticSystemParam, SystemId, SystemParamItem,
+    },
+    world::World,
 };
 
 use crate::{
     access::{CurrentRef, FlushRef, NextRef},
-    schedule::ResolveStateSystems,
-    state::State,
+    next_state::PreviousState,
+    schedule::{
+        ApplyFlushSystems, ResolveStateSystems, StateFlushFilter,
+        guard_flush::guard_flush,
+        observe_flush::{EnterState, ExitState, TransState},
+    },
+    state::{State, StateMut},
 };
 
 /// A type that can match a subset of values of the [`State`] type `S`.
@@ -28,6 +43,54 @@ pub trait StatePattern<S: State>: 'static + Send + Sync + Sized {
     /// Check if the pattern matches a particular state.
     fn matches(&self, state: &S) -> bool;
 
+    /// Build a pattern that matches if both this pattern and `other` match.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+    /// # struct Level(usize);
+    /// #
+    /// # fn is_boss(level: &Level) -> bool { level.0 % 5 == 0 }
+    /// # fn play_boss_music() {}
+    /// #
+    /// # fn plugin(app: &mut App) {
+    /// app.add_systems(StateFlush, Level::with(is_boss).and(state!(Level(5..))).on_enter(play_boss_music));
+    /// # }
+    /// ```
+    fn and<P: StatePattern<S>>(self, other: P) -> AndPattern<Self, P> {
+        AndPattern(self, other)
+    }
+
+    /// Build a pattern that matches if either this pattern or `other` matches.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+    /// # enum Screen {
+    /// #     Title,
+    /// #     Credits,
+    /// #     Gameplay,
+    /// # }
+    /// #
+    /// # fn play_menu_music() {}
+    /// #
+    /// # fn plugin(app: &mut App) {
+    /// app.add_systems(StateFlush, Screen::Title.or(Screen::Credits).on_enter(play_menu_music));
+    /// # }
+    /// ```
+    fn or<P: StatePattern<S>>(self, other: P) -> OrPattern<Self, P> {
+        OrPattern(self, other)
+    }
+
+    /// Build a pattern that matches if this pattern does not match.
+    fn not(self) -> NotPattern<Self> {
+        NotPattern(self)
+    }
+
     /// Build a run condition that checks if `S` is in a matching state.
     fn will_update(self) -> impl 'static + Send + Sync + Fn(CurrentRef<S>) -> bool {
         self.will_exit()
@@ -47,27 +110,44 @@ pub trait StatePattern<S: State>: 'static + Send + Sync + Sized {
     }
 
     /// Configure systems to run when `S` exits a matching state.
+    ///
+    /// The systems can take the old state as `In<S>` or `InRef<S>` if `S` implements `Clone`.
     fn on_exit<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, ExitHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_exit())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Exit)
     }
 
+    /// Build an [`Observer`] that runs when `S` exits a matching state.
+    ///
+    /// `S` must opt in with the `observe_flush` [derive macro option](pyri_state_derive::State).
+    /// See [`ExitState`] for more information.
+    fn observe_exit<B: Bundle, M>(
+        self,
+        observer: impl IntoObserverSystem<ExitState<S>, B, M>,
+    ) -> Observer {
+        Observer::new(observer).run_if(self.will_exit())
+    }
+
     /// Build a run condition that checks if `S` will become disabled from a matching state if triggered.
     fn will_disable(self) -> impl 'static + Send + Sync + Fn(FlushRef<S>) -> bool {
         move |state| state.will_disable(&self)
     }
 
     /// Configure systems to run when `S` is disabled from a matching state.
+    ///
+    /// The systems can take the old state as `In<S>` or `InRef<S>` if `S` implements `Clone`.
     fn on_disable<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, ExitHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_disable())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Exit)
@@ -79,31 +159,131 @@ pub trait StatePattern<S: State>: 'static + Send + Sync + Sized {
     }
 
     /// Configure systems to run when `S` enters a matching state.
+    ///
+    /// The systems can take the new state as `In<S>` or `InRef<S>` if `S` implements `Clone`.
     fn on_enter<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, EnterHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_enter())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Enter)
     }
 
+    /// Build an [`Observer`] that runs when `S` enters a matching state.
+    ///
+    /// `S` must opt in with the `observe_flush` [derive macro option](pyri_state_derive::State).
+    /// See [`EnterState`] for more information.
+    fn observe_enter<B: Bundle, M>(
+        self,
+        observer: impl IntoObserverSystem<EnterState<S>, B, M>,
+    ) -> Observer {
+        Observer::new(observer).run_if(self.will_enter())
+    }
+
     /// Build a run condition that checks if `S` will become enabled in a matching state if triggered.
     fn will_enable(self) -> impl 'static + Send + Sync + Fn(FlushRef<S>) -> bool {
         move |state| state.will_enable(&self)
     }
 
     /// Configure systems to run when `S` becomes enabled in a matching state.
+    ///
+    /// The systems can take the new state as `In<S>` or `InRef<S>` if `S` implements `Clone`.
     fn on_enable<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, EnterHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(S::is_triggered.and_then(self.will_enable()))
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Enter)
     }
+
+    /// Configure a guard system that can reject `S` becoming enabled in a matching state by
+    /// returning `false`.
+    ///
+    /// See [`guard_flush`] for more information.
+    fn guard_enable<M>(self, check: impl IntoSystem<(), bool, M>) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: StateMut + Clone,
+    {
+        guard_flush::<S, M>(check).run_if(self.will_enable())
+    }
+
+    /// Configure a guard system that can reject `S` becoming disabled from a matching state by
+    /// returning `false`.
+    ///
+    /// See [`guard_flush`] for more information.
+    fn guard_disable<M>(
+        self,
+        check: impl IntoSystem<(), bool, M>,
+    ) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: StateMut + Clone,
+    {
+        guard_flush::<S, M>(check).run_if(self.will_disable())
+    }
+
+    /// Configure systems to run after a flush of `S` out of a matching state has been applied.
+    ///
+    /// Unlike [`on_exit`](Self::on_exit), the systems run in
+    /// [`ResolveStateSystems::<S>::Applied`], so they will see the new current state. The exited
+    /// state is recorded in a [`PreviousState<S>`] resource.
+    fn on_exited<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: Clone,
+    {
+        (
+            record_previous_state::<S>.in_set(ResolveStateSystems::<S>::AnyFlush),
+            systems
+                .run_if(move |previous: Option<Res<PreviousState<S>>>| {
+                    matches!(previous.as_deref(), Some(PreviousState(Some(x))) if self.matches(x))
+                })
+                .in_set(ResolveStateSystems::<S>::Applied),
+        )
+            .into_configs()
+    }
+
+    /// Configure systems to run after a flush of `S` into a matching state has been applied.
+    ///
+    /// Unlike [`on_enter`](Self::on_enter), the systems run in
+    /// [`ResolveStateSystems::<S>::Applied`], so they can read the new current state with
+    /// [`CurrentRef<S>`] or `Res<S>`.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+    /// # struct Level(usize);
+    /// #
+    /// fn spawn_level(level: Res<Level>) {
+    ///     info!("Spawning level {}", level.0);
+    /// }
+    ///
+    /// # fn plugin(app: &mut App) {
+    /// app.add_systems(StateFlush, Level::ANY.on_entered(spawn_level));
+    /// # }
+    /// ```
+    fn on_entered<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems
+            .run_if(self.will_update())
+            .in_set(ResolveStateSystems::<S>::Applied)
+    }
+}
+
+// Record the current state before it's replaced by the flush for `on_exited` hooks.
+fn record_previous_state<S: State + Clone>(mut commands: Commands, state: CurrentRef<S>) {
+    commands.insert_resource(PreviousState(state.get().cloned()));
 }
 
 /// An extension trait for [`StatePattern`] types that also implement `Clone`.
@@ -112,8 +292,8 @@ pub trait StatePatternExtClone<S: State>: StatePattern<S> + Clone {
     /// [`on_enter`](StatePattern::on_enter) systems for the same `StatePattern`.
     fn on_edge<M1, M2>(
         self,
-        exit_systems: impl IntoScheduleConfigs<ScheduleSystem, M1>,
-        enter_systems: impl IntoScheduleConfigs<ScheduleSystem, M2>,
+        exit_systems: impl IntoStateHookConfigs<S, ExitHook, M1>,
+        enter_systems: impl IntoStateHookConfigs<S, EnterHook, M2>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         (
             self.clone().on_exit(exit_systems),
@@ -146,6 +326,51 @@ pub trait StatePatternExtEq<S: State + Eq>: StatePattern<S> {
 
 impl<S: State + Eq, P: StatePattern<S>> StatePatternExtEq<S> for P {}
 
+/// An extension trait for [`State`] types that also implement `Ord`.
+///
+/// Each bound accepts any value that converts into the state type, e.g. `Level::at_least(5)` if
+/// `Level` implements `From<usize>`.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// # struct Level(usize);
+/// #
+/// impl From<usize> for Level {
+///     fn from(value: usize) -> Self {
+///         Self(value)
+///     }
+/// }
+///
+/// # fn spawn_elites() {}
+/// # fn show_tutorial() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, Level::at_least(5).on_enter(spawn_elites));
+/// app.add_systems(StateFlush, Level::below(Level(2)).on_enter(show_tutorial));
+/// # }
+/// ```
+pub trait StatePatternExtOrd: State + Ord {
+    /// Build a pattern that matches states greater than or equal to `min`.
+    fn at_least(min: impl Into<Self>) -> RangeStatePattern<Self> {
+        RangeStatePattern::new(Bound::Included(min.into()), Bound::Unbounded)
+    }
+
+    /// Build a pattern that matches states from `min` to `max` (inclusive).
+    fn between(min: impl Into<Self>, max: impl Into<Self>) -> RangeStatePattern<Self> {
+        RangeStatePattern::new(Bound::Included(min.into()), Bound::Included(max.into()))
+    }
+
+    /// Build a pattern that matches states less than `max`.
+    fn below(max: impl Into<Self>) -> RangeStatePattern<Self> {
+        RangeStatePattern::new(Bound::Unbounded, Bound::Excluded(max.into()))
+    }
+}
+
+impl<S: State + Ord> StatePatternExtOrd for S {}
+
 impl<S: State + Eq> StatePattern<S> for S {
     fn matches(&self, state: &S) -> bool {
         self == state
@@ -169,9 +394,9 @@ impl<S: State + Eq> StatePattern<S> for S {
 /// app.add_systems(StateFlush, Level::ANY.on_enter(reset_timer));
 /// # }
 /// ```
-pub struct AnyStatePattern<S: State>(pub(crate) PhantomData<S>);
+pub struct AnyStatePattern<S>(pub(crate) PhantomData<S>);
 
-impl<S: State> Clone for AnyStatePattern<S> {
+impl<S> Clone for AnyStatePattern<S> {
     fn clone(&self) -> Self {
         Self(PhantomData)
     }
@@ -206,7 +431,7 @@ impl<S: State> StatePattern<S> for AnyStatePattern<S> {
 /// # }
 /// ```
 #[derive(Clone)]
-pub struct FnStatePattern<S: State, F>(F, PhantomData<S>)
+pub struct FnStatePattern<S, F>(pub(crate) F, PhantomData<S>)
 where
     F: 'static + Send + Sync + Fn(&S) -> bool;
 
@@ -219,7 +444,7 @@ where
     }
 }
 
-impl<S: State, F> FnStatePattern<S, F>
+impl<S, F> FnStatePattern<S, F>
 where
     F: 'static + Send + Sync + Fn(&S) -> bool,
 {
@@ -229,6 +454,42 @@ where
     }
 }
 
+/// A [`StatePattern`] that matches values of the [`State`] type `S` within a range.
+///
+/// The usual way to construct this type is with [`StatePatternExtOrd`]:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// # struct Level(usize);
+/// #
+/// # fn play_hard_music() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, Level::between(Level(3), Level(7)).on_enter(play_hard_music));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RangeStatePattern<S> {
+    start: Bound<S>,
+    end: Bound<S>,
+}
+
+impl<S: State + Ord> StatePattern<S> for RangeStatePattern<S> {
+    fn matches(&self, state: &S) -> bool {
+        (self.start.as_ref(), self.end.as_ref()).contains(state)
+    }
+}
+
+impl<S> RangeStatePattern<S> {
+    /// Create a new `RangeStatePattern` from its start and end bounds.
+    pub fn new(start: Bound<S>, end: Bound<S>) -> Self {
+        Self { start, end }
+    }
+}
+
 /// A type that can match a subset of transitions in the [`State`] type `S`.
 ///
 /// A tuple of two [`StatePattern`] types can be used as a transition pattern.
@@ -236,47 +497,142 @@ where
 /// See the following extension traits with additional bounds on `Self`:
 ///
 /// - [`StateTransPatternExtClone`]
+/// - [`StateTransPatternExtOrd`]
 pub trait StateTransPattern<S: State>: 'static + Send + Sync + Sized {
     /// Check if the pattern matches a particular pair of states.
     fn matches(&self, old: &S, new: &S) -> bool;
 
+    /// Build a pattern that matches if both this pattern and `other` match.
+    fn and<P: StateTransPattern<S>>(self, other: P) -> AndTransPattern<Self, P> {
+        AndTransPattern(self, other)
+    }
+
+    /// Build a pattern that matches if either this pattern or `other` matches.
+    fn or<P: StateTransPattern<S>>(self, other: P) -> OrTransPattern<Self, P> {
+        OrTransPattern(self, other)
+    }
+
+    /// Build a pattern that matches if this pattern does not match.
+    fn not(self) -> NotTransPattern<Self> {
+        NotTransPattern(self)
+    }
+
     /// Build a run condition that checks if `S` will undergo a matching transition if triggered.
     fn will_trans(self) -> impl 'static + Send + Sync + Fn(FlushRef<S>) -> bool {
         move |state| state.will_trans(&self)
     }
 
     /// Configure systems to run when `S` exits as part of a matching transition.
+    ///
+    /// The systems can take the old and new states as `In<(S, S)>` or `InRef<(S, S)>` if `S`
+    /// implements `Clone`.
     fn on_exit<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, TransHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_trans())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Exit)
     }
 
     /// Configure systems to run when `S` undergoes a matching transition.
+    ///
+    /// The systems can take the old and new states as `In<(S, S)>` or `InRef<(S, S)>` if `S`
+    /// implements `Clone`.
     fn on_trans<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, TransHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_trans())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Trans)
     }
 
     /// Configure systems to run when `S` enters as part of a matching transition.
+    ///
+    /// The systems can take the old and new states as `In<(S, S)>` or `InRef<(S, S)>` if `S`
+    /// implements `Clone`.
     fn on_enter<M>(
         self,
-        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+        systems: impl IntoStateHookConfigs<S, TransHook, M>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         systems
+            .into_hook_configs()
             .run_if(self.will_trans())
             .in_set(ResolveStateSystems::<S>::AnyFlush)
             .in_set(ResolveStateSystems::<S>::Enter)
     }
+
+    /// Build an [`Observer`] that runs when `S` exits as part of a matching transition.
+    ///
+    /// `S` must opt in with the `observe_flush` [derive macro option](pyri_state_derive::State).
+    /// See [`ExitState`] for more information.
+    fn observe_exit<B: Bundle, M>(
+        self,
+        observer: impl IntoObserverSystem<ExitState<S>, B, M>,
+    ) -> Observer {
+        Observer::new(observer).run_if(self.will_trans())
+    }
+
+    /// Build an [`Observer`] that runs when `S` undergoes a matching transition.
+    ///
+    /// `S` must opt in with the `observe_flush` [derive macro option](pyri_state_derive::State).
+    /// See [`TransState`] for more information.
+    fn observe_trans<B: Bundle, M>(
+        self,
+        observer: impl IntoObserverSystem<TransState<S>, B, M>,
+    ) -> Observer {
+        Observer::new(observer).run_if(self.will_trans())
+    }
+
+    /// Build an [`Observer`] that runs when `S` enters as part of a matching transition.
+    ///
+    /// `S` must opt in with the `observe_flush` [derive macro option](pyri_state_derive::State).
+    /// See [`EnterState`] for more information.
+    fn observe_enter<B: Bundle, M>(
+        self,
+        observer: impl IntoObserverSystem<EnterState<S>, B, M>,
+    ) -> Observer {
+        Observer::new(observer).run_if(self.will_trans())
+    }
+
+    /// Configure a guard system that can reject a matching transition by returning `false`.
+    ///
+    /// A transition is a flush between two enabled states, so this can't reject `S` becoming
+    /// enabled or disabled. Use [`StatePattern::guard_enable`], [`StatePattern::guard_disable`],
+    /// or [`StateFlushPattern::guard`] for that instead.
+    ///
+    /// See [`guard_flush`] for more information.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+    /// # #[state(guard_flush)]
+    /// # struct Level(usize);
+    /// #
+    /// # #[derive(Resource)]
+    /// # struct Unlocked(usize);
+    /// #
+    /// fn is_unlocked(level: NextRef<Level>, unlocked: Res<Unlocked>) -> bool {
+    ///     level.unwrap().0 <= unlocked.0
+    /// }
+    ///
+    /// # fn plugin(app: &mut App) {
+    /// app.add_systems(StateFlush, Level::when(|x, y| y.0 > x.0).guard(is_unlocked));
+    /// # }
+    /// ```
+    fn guard<M>(self, check: impl IntoSystem<(), bool, M>) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: StateMut + Clone,
+    {
+        guard_flush::<S, M>(check).run_if(self.will_trans())
+    }
 }
 
 /// An extension trait for [`StateTransPattern`] types that also implement `Clone`.
@@ -285,8 +641,8 @@ pub trait StateTransPatternExtClone<S: State>: StateTransPattern<S> + Clone {
     /// [`on_enter`](StateTransPattern::on_enter) systems for the same `StateTransPattern`.
     fn on_edge<M1, M2>(
         self,
-        exit_systems: impl IntoScheduleConfigs<ScheduleSystem, M1>,
-        enter_systems: impl IntoScheduleConfigs<ScheduleSystem, M2>,
+        exit_systems: impl IntoStateHookConfigs<S, TransHook, M1>,
+        enter_systems: impl IntoStateHookConfigs<S, TransHook, M2>,
     ) -> ScheduleConfigs<ScheduleSystem> {
         (
             self.clone().on_exit(exit_systems),
@@ -298,6 +654,37 @@ pub trait StateTransPatternExtClone<S: State>: StateTransPattern<S> + Clone {
 
 impl<S: State, P: StateTransPattern<S> + Clone> StateTransPatternExtClone<S> for P {}
 
+/// An extension trait for [`StateTransPattern<S>`] when `S` also implements `Ord`.
+pub trait StateTransPatternExtOrd<S: State + Ord>: StateTransPattern<S> {
+    /// Build a pattern that matches if this pattern matches and the new state is greater than
+    /// the old state.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    /// # struct Level(usize);
+    /// #
+    /// # fn play_level_up_sfx() {}
+    /// #
+    /// # fn plugin(app: &mut App) {
+    /// app.add_systems(StateFlush, Level::ANY_TO_ANY.increasing().on_enter(play_level_up_sfx));
+    /// # }
+    /// ```
+    fn increasing(self) -> AndTransPattern<Self, FnStateTransPattern<S, fn(&S, &S) -> bool>> {
+        self.and(FnStateTransPattern::new(|old, new| old < new))
+    }
+
+    /// Build a pattern that matches if this pattern matches and the new state is less than
+    /// the old state.
+    fn decreasing(self) -> AndTransPattern<Self, FnStateTransPattern<S, fn(&S, &S) -> bool>> {
+        self.and(FnStateTransPattern::new(|old, new| old > new))
+    }
+}
+
+impl<S: State + Ord, P: StateTransPattern<S>> StateTransPatternExtOrd<S> for P {}
+
 impl<S: State, P1: StatePattern<S>, P2: StatePattern<S>> StateTransPattern<S> for (P1, P2) {
     fn matches(&self, old: &S, new: &S) -> bool {
         self.0.matches(old) && self.1.matches(new)
@@ -326,7 +713,7 @@ impl<S: State, P1: StatePattern<S>, P2: StatePattern<S>> StateTransPattern<S> fo
 /// ```
 ///
 #[derive(Clone)]
-pub struct AnyStateTransPattern<S: State>(pub(crate) PhantomData<S>);
+pub struct AnyStateTransPattern<S>(pub(crate) PhantomData<S>);
 
 impl<S: State> StateTransPattern<S> for AnyStateTransPattern<S> {
     fn matches(&self, _old: &S, _new: &S) -> bool {
@@ -356,7 +743,7 @@ impl<S: State> StateTransPattern<S> for AnyStateTransPattern<S> {
 /// # }
 /// ```
 #[derive(Clone)]
-pub struct FnStateTransPattern<S: State, F>(F, PhantomData<S>)
+pub struct FnStateTransPattern<S, F>(pub(crate) F, PhantomData<S>)
 where
     F: 'static + Send + Sync + Fn(&S, &S) -> bool;
 
@@ -369,7 +756,7 @@ where
     }
 }
 
-impl<S: State, F> FnStateTransPattern<S, F>
+impl<S, F> FnStateTransPattern<S, F>
 where
     F: 'static + Send + Sync + Fn(&S, &S) -> bool,
 {
@@ -379,11 +766,186 @@ where
     }
 }
 
-/// A macro for building pattern-matching [`FnStatePattern`] and [`FnStateTransPattern`] instances.
+/// A type that can match a subset of flushes in the [`State`] type `S`, including flushes that
+/// enable or disable `S`.
 ///
-/// # Examples
+/// Unlike [`StateTransPattern`], which only matches flushes from an enabled state to an enabled
+/// state, a flush pattern receives `None` for a disabled state.
+pub trait StateFlushPattern<S: State>: 'static + Send + Sync + Sized {
+    /// Check if the pattern matches a particular pair of states, or `None` if disabled.
+    fn matches(&self, old: Option<&S>, new: Option<&S>) -> bool;
+
+    /// Build a pattern that matches if both this pattern and `other` match.
+    fn and<P: StateFlushPattern<S>>(self, other: P) -> AndFlushPattern<Self, P> {
+        AndFlushPattern(self, other)
+    }
+
+    /// Build a pattern that matches if either this pattern or `other` matches.
+    fn or<P: StateFlushPattern<S>>(self, other: P) -> OrFlushPattern<Self, P> {
+        OrFlushPattern(self, other)
+    }
+
+    /// Build a pattern that matches if this pattern does not match.
+    fn not(self) -> NotFlushPattern<Self> {
+        NotFlushPattern(self)
+    }
+
+    /// Build a run condition that checks if `S` will undergo a matching flush if triggered.
+    fn will_flush(self) -> impl 'static + Send + Sync + Fn(FlushRef<S>) -> bool {
+        move |state| state.will_flush(&self)
+    }
+
+    /// Configure systems to run when `S` exits as part of a matching flush.
+    ///
+    /// The systems can take the old and new states as `In<(Option<S>, Option<S>)>` or
+    /// `InRef<(Option<S>, Option<S>)>` if `S` implements `Clone`.
+    fn on_exit<M>(
+        self,
+        systems: impl IntoStateHookConfigs<S, FlushHook, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems
+            .into_hook_configs()
+            .run_if(self.will_flush())
+            .in_set(ResolveStateSystems::<S>::AnyFlush)
+            .in_set(ResolveStateSystems::<S>::Exit)
+    }
+
+    /// Configure systems to run when `S` undergoes a matching flush.
+    ///
+    /// The systems can take the old and new states as `In<(Option<S>, Option<S>)>` or
+    /// `InRef<(Option<S>, Option<S>)>` if `S` implements `Clone`.
+    fn on_trans<M>(
+        self,
+        systems: impl IntoStateHookConfigs<S, FlushHook, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems
+            .into_hook_configs()
+            .run_if(self.will_flush())
+            .in_set(ResolveStateSystems::<S>::AnyFlush)
+            .in_set(ResolveStateSystems::<S>::Trans)
+    }
+
+    /// Configure systems to run when `S` enters as part of a matching flush.
+    ///
+    /// The systems can take the old and new states as `In<(Option<S>, Option<S>)>` or
+    /// `InRef<(Option<S>, Option<S>)>` if `S` implements `Clone`.
+    fn on_enter<M>(
+        self,
+        systems: impl IntoStateHookConfigs<S, FlushHook, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems
+            .into_hook_configs()
+            .run_if(self.will_flush())
+            .in_set(ResolveStateSystems::<S>::AnyFlush)
+            .in_set(ResolveStateSystems::<S>::Enter)
+    }
+
+    /// Configure a guard system that can reject a matching flush by returning `false`.
+    ///
+    /// Unlike [`StateTransPattern::guard`], this can also reject `S` becoming enabled or
+    /// disabled.
+    ///
+    /// See [`guard_flush`] for more information.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+    /// # #[state(guard_flush)]
+    /// # struct Level(usize);
+    /// #
+    /// # fn is_saving() -> bool { false }
+    /// #
+    /// # fn plugin(app: &mut App) {
+    /// // Don't leave the current level or disable `Level` while saving.
+    /// app.add_systems(StateFlush, Level::when_flush(|old, _| old.is_some()).guard(|| !is_saving()));
+    /// # }
+    /// ```
+    fn guard<M>(self, check: impl IntoSystem<(), bool, M>) -> ScheduleConfigs<ScheduleSystem>
+    where
+        S: StateMut + Clone,
+    {
+        guard_flush::<S, M>(check).run_if(self.will_flush())
+    }
+}
+
+/// A [`StateFlushPattern`] that runs a callback to determine which flushes
+/// of the [`State`] type `S` should match.
 ///
-/// State pattern-matching:
+/// The usual way to construct this type is with the [`state!`](crate::state!) macro or
+/// [`State::when_flush`]:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn play_intro_cutscene() {}
+/// # fn show_game_over() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, state!(None => Level(1)).on_enter(play_intro_cutscene));
+/// app.add_systems(StateFlush, Level::when_flush(|x, y| x.is_some() && y.is_none()).on_exit(show_game_over));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FnStateFlushPattern<S, F>(F, PhantomData<S>)
+where
+    F: 'static + Send + Sync + Fn(Option<&S>, Option<&S>) -> bool;
+
+impl<S: State, F> StateFlushPattern<S> for FnStateFlushPattern<S, F>
+where
+    F: 'static + Send + Sync + Fn(Option<&S>, Option<&S>) -> bool,
+{
+    fn matches(&self, old: Option<&S>, new: Option<&S>) -> bool {
+        self.0(old, new)
+    }
+}
+
+impl<S, F> FnStateFlushPattern<S, F>
+where
+    F: 'static + Send + Sync + Fn(Option<&S>, Option<&S>) -> bool,
+{
+    /// Create a new `FnStateFlushPattern`.
+    pub fn new(f: F) -> Self {
+        Self(f, PhantomData)
+    }
+}
+
+/// A [`StatePattern`] that matches if both inner patterns match.
+///
+/// The usual way to construct this type is with [`StatePattern::and`] or the `&` operator on a
+/// pattern type from this module:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn spawn_boss() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, (state!(Level(5..)) & Level::with(|x| x.0 % 5 == 0)).on_enter(spawn_boss));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AndPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StatePattern<S>, P2: StatePattern<S>> StatePattern<S> for AndPattern<P1, P2> {
+    fn matches(&self, state: &S) -> bool {
+        self.0.matches(state) && self.1.matches(state)
+    }
+}
+
+/// A [`StatePattern`] that matches if either inner pattern matches.
+///
+/// The usual way to construct this type is with [`StatePattern::or`] or the `|` operator on a
+/// pattern type from this module:
 ///
 /// ```
 /// # use bevy::prelude::*;
@@ -395,11 +957,22 @@ where
 /// # fn save_checkpoint() {}
 /// #
 /// # fn plugin(app: &mut App) {
-/// app.add_systems(StateFlush, state!(Level(4 | 7 | 10)).on_enter(save_checkpoint));
+/// app.add_systems(StateFlush, (state!(Level(4 | 7)) | Level::with(|x| x.0 > 10)).on_enter(save_checkpoint));
 /// # }
 /// ```
+#[derive(Clone)]
+pub struct OrPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StatePattern<S>, P2: StatePattern<S>> StatePattern<S> for OrPattern<P1, P2> {
+    fn matches(&self, state: &S) -> bool {
+        self.0.matches(state) || self.1.matches(state)
+    }
+}
+
+/// A [`StatePattern`] that matches if the inner pattern does not match.
 ///
-/// State transition pattern-matching:
+/// The usual way to construct this type is with [`StatePattern::not`] or the `!` operator on a
+/// pattern type from this module:
 ///
 /// ```
 /// # use bevy::prelude::*;
@@ -408,23 +981,925 @@ where
 /// # #[derive(State, Resource, Clone, PartialEq, Eq)]
 /// # struct Level(usize);
 /// #
-/// # fn do_something_cool() {}
+/// # fn play_level_music() {}
 /// #
 /// # fn plugin(app: &mut App) {
-/// app.add_systems(StateFlush, state!(Level(x @ 1..=3) => y if y.0 == 10 - x).on_trans(do_something_cool));
+/// app.add_systems(StateFlush, (!state!(Level(0))).on_enter(play_level_music));
 /// # }
 /// ```
-#[macro_export]
-macro_rules! state {
-    ($state:pat $(if $guard:expr)? $(,)?) => {
-        pyri_state::pattern::FnStatePattern::new(
-            |state| matches!(state, $state $(if $guard)?),
-        )
-    };
+#[derive(Clone)]
+pub struct NotPattern<P>(pub P);
 
-    ($old:pat => $new:pat $(if $guard:expr)? $(,)?) => {
-        pyri_state::pattern::FnStateTransPattern::new(
-            |old, new| matches!((old, new), ($old, $new) $(if $guard)?),
-        )
-    };
+impl<S: State, P: StatePattern<S>> StatePattern<S> for NotPattern<P> {
+    fn matches(&self, state: &S) -> bool {
+        !self.0.matches(state)
+    }
+}
+
+/// A [`StateTransPattern`] that matches if both inner patterns match.
+///
+/// The usual way to construct this type is with [`StateTransPattern::and`] or the `&` operator
+/// on a pattern type from this module:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn play_level_up_sfx() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, (Level::ANY_TO_ANY & Level::when(|x, y| x.0 < y.0)).on_enter(play_level_up_sfx));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AndTransPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StateTransPattern<S>, P2: StateTransPattern<S>> StateTransPattern<S>
+    for AndTransPattern<P1, P2>
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        self.0.matches(old, new) && self.1.matches(old, new)
+    }
+}
+
+/// A [`StateTransPattern`] that matches if either inner pattern matches.
+///
+/// The usual way to construct this type is with [`StateTransPattern::or`] or the `|` operator
+/// on a pattern type from this module.
+#[derive(Clone)]
+pub struct OrTransPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StateTransPattern<S>, P2: StateTransPattern<S>> StateTransPattern<S>
+    for OrTransPattern<P1, P2>
+{
+    fn matches(&self, old: &S, new: &S) -> bool {
+        self.0.matches(old, new) || self.1.matches(old, new)
+    }
+}
+
+/// A [`StateTransPattern`] that matches if the inner pattern does not match.
+///
+/// The usual way to construct this type is with [`StateTransPattern::not`] or the `!` operator
+/// on a pattern type from this module.
+#[derive(Clone)]
+pub struct NotTransPattern<P>(pub P);
+
+impl<S: State, P: StateTransPattern<S>> StateTransPattern<S> for NotTransPattern<P> {
+    fn matches(&self, old: &S, new: &S) -> bool {
+        !self.0.matches(old, new)
+    }
+}
+
+/// A [`StateFlushPattern`] that matches if both inner patterns match.
+///
+/// The usual way to construct this type is with [`StateFlushPattern::and`] or the `&` operator
+/// on a pattern type from this module:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn play_intro_cutscene() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, (state!(None => _) & Level::when_flush(|_, y| y.is_some_and(|y| y.0 == 1))).on_enter(play_intro_cutscene));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AndFlushPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StateFlushPattern<S>, P2: StateFlushPattern<S>> StateFlushPattern<S>
+    for AndFlushPattern<P1, P2>
+{
+    fn matches(&self, old: Option<&S>, new: Option<&S>) -> bool {
+        self.0.matches(old, new) && self.1.matches(old, new)
+    }
+}
+
+/// A [`StateFlushPattern`] that matches if either inner pattern matches.
+///
+/// The usual way to construct this type is with [`StateFlushPattern::or`] or the `|` operator
+/// on a pattern type from this module.
+#[derive(Clone)]
+pub struct OrFlushPattern<P1, P2>(pub P1, pub P2);
+
+impl<S: State, P1: StateFlushPattern<S>, P2: StateFlushPattern<S>> StateFlushPattern<S>
+    for OrFlushPattern<P1, P2>
+{
+    fn matches(&self, old: Option<&S>, new: Option<&S>) -> bool {
+        self.0.matches(old, new) || self.1.matches(old, new)
+    }
+}
+
+/// A [`StateFlushPattern`] that matches if the inner pattern does not match.
+///
+/// The usual way to construct this type is with [`StateFlushPattern::not`] or the `!` operator
+/// on a pattern type from this module.
+#[derive(Clone)]
+pub struct NotFlushPattern<P>(pub P);
+
+impl<S: State, P: StateFlushPattern<S>> StateFlushPattern<S> for NotFlushPattern<P> {
+    fn matches(&self, old: Option<&S>, new: Option<&S>) -> bool {
+        !self.0.matches(old, new)
+    }
+}
+
+// Implement the `&`, `|`, and `!` operators for the pattern types in this module.
+//
+// These can't be implemented for arbitrary pattern types (e.g. `State` types used directly as
+// patterns), so use `and`, `or`, and `not` for those instead.
+macro_rules! impl_pattern_ops {
+    ($and:ident, $or:ident, $not:ident: $ty:ident<$($param:ident),*> $(where $($bound:tt)*)?) => {
+        impl<$($param,)* Rhs> BitAnd<Rhs> for $ty<$($param),*> $(where $($bound)*)? {
+            type Output = $and<Self, Rhs>;
+
+            fn bitand(self, rhs: Rhs) -> Self::Output {
+                $and(self, rhs)
+            }
+        }
+
+        impl<$($param,)* Rhs> BitOr<Rhs> for $ty<$($param),*> $(where $($bound)*)? {
+            type Output = $or<Self, Rhs>;
+
+            fn bitor(self, rhs: Rhs) -> Self::Output {
+                $or(self, rhs)
+            }
+        }
+
+        impl<$($param),*> Not for $ty<$($param),*> $(where $($bound)*)? {
+            type Output = $not<Self>;
+
+            fn not(self) -> Self::Output {
+                $not(self)
+            }
+        }
+    };
+}
+
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: AnyStatePattern<S>);
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: FnStatePattern<S, F> where F: 'static + Send + Sync + Fn(&S) -> bool);
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: RangeStatePattern<S>);
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: AndPattern<P1, P2>);
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: OrPattern<P1, P2>);
+impl_pattern_ops!(AndPattern, OrPattern, NotPattern: NotPattern<P>);
+impl_pattern_ops!(AndTransPattern, OrTransPattern, NotTransPattern: AnyStateTransPattern<S>);
+impl_pattern_ops!(AndTransPattern, OrTransPattern, NotTransPattern: FnStateTransPattern<S, F> where F: 'static + Send + Sync + Fn(&S, &S) -> bool);
+impl_pattern_ops!(AndTransPattern, OrTransPattern, NotTransPattern: AndTransPattern<P1, P2>);
+impl_pattern_ops!(AndTransPattern, OrTransPattern, NotTransPattern: OrTransPattern<P1, P2>);
+impl_pattern_ops!(AndTransPattern, OrTransPattern, NotTransPattern: NotTransPattern<P>);
+impl_pattern_ops!(AndFlushPattern, OrFlushPattern, NotFlushPattern: FnStateFlushPattern<S, F> where F: 'static + Send + Sync + Fn(Option<&S>, Option<&S>) -> bool);
+impl_pattern_ops!(AndFlushPattern, OrFlushPattern, NotFlushPattern: AndFlushPattern<P1, P2>);
+impl_pattern_ops!(AndFlushPattern, OrFlushPattern, NotFlushPattern: OrFlushPattern<P1, P2>);
+impl_pattern_ops!(AndFlushPattern, OrFlushPattern, NotFlushPattern: NotFlushPattern<P>);
+
+/// A tuple of [`StatePattern`] types for different [`State`] types, used in [`JointPattern`].
+///
+/// Implemented for tuples of up to 8 state patterns.
+pub trait StatePatternTuple<S>: 'static + Send + Sync {
+    /// A system parameter with read-only access to the current and next values of each
+    /// `State` type.
+    type Param: ReadOnlySystemParam;
+
+    /// Check if every `State` type is in a state matching its pattern.
+    fn is_in(&self, param: &SystemParamItem<Self::Param>) -> bool;
+
+    /// Check if every `State` type will be in a state matching its pattern after any triggered
+    /// flushes allowed by the [`StateFlushFilter`] are applied.
+    fn will_be_in(
+        &self,
+        param: &SystemParamItem<Self::Param>,
+        filter: Option<&StateFlushFilter>,
+    ) -> bool;
+
+    /// Configure systems to run after every `State` type has been resolved.
+    fn after_resolve<M>(
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem>;
+}
+
+fn will_be_in_after_flush<S: State, P: StatePattern<S>>(
+    state: &FlushRef<S>,
+    pattern: &P,
+    filter: Option<&StateFlushFilter>,
+) -> bool {
+    if state.next.is_triggered() && filter.is_none_or(|x| x.allows::<S>()) {
+        state.next.will_be_in(pattern)
+    } else {
+        state.current.is_in(pattern)
+    }
+}
+
+macro_rules! impl_state_pattern_tuple {
+    ($(($S:ident, $P:ident, $p:ident, $s:ident)),+) => {
+        impl<$($S: State, $P: StatePattern<$S>),+> StatePatternTuple<($($S,)+)> for ($($P,)+) {
+            type Param = ($(FlushRef<'static, 'static, $S>,)+);
+
+            fn is_in(&self, param: &SystemParamItem<Self::Param>) -> bool {
+                let ($($p,)+) = self;
+                let ($($s,)+) = param;
+                true $(&& $s.current.is_in($p))+
+            }
+
+            fn will_be_in(
+                &self,
+                param: &SystemParamItem<Self::Param>,
+                filter: Option<&StateFlushFilter>,
+            ) -> bool {
+                let ($($p,)+) = self;
+                let ($($s,)+) = param;
+                true $(&& will_be_in_after_flush($s, $p, filter))+
+            }
+
+            fn after_resolve<M>(
+                systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+            ) -> ScheduleConfigs<ScheduleSystem> {
+                systems $(.after(ResolveStateSystems::<$S>::Resolve))+
+            }
+        }
+    };
+}
+
+impl_state_pattern_tuple!((S1, P1, p1, s1));
+impl_state_pattern_tuple!((S1, P1, p1, s1), (S2, P2, p2, s2));
+impl_state_pattern_tuple!((S1, P1, p1, s1), (S2, P2, p2, s2), (S3, P3, p3, s3));
+impl_state_pattern_tuple!(
+    (S1, P1, p1, s1),
+    (S2, P2, p2, s2),
+    (S3, P3, p3, s3),
+    (S4, P4, p4, s4)
+);
+impl_state_pattern_tuple!(
+    (S1, P1, p1, s1),
+    (S2, P2, p2, s2),
+    (S3, P3, p3, s3),
+    (S4, P4, p4, s4),
+    (S5, P5, p5, s5)
+);
+impl_state_pattern_tuple!(
+    (S1, P1, p1, s1),
+    (S2, P2, p2, s2),
+    (S3, P3, p3, s3),
+    (S4, P4, p4, s4),
+    (S5, P5, p5, s5),
+    (S6, P6, p6, s6)
+);
+impl_state_pattern_tuple!(
+    (S1, P1, p1, s1),
+    (S2, P2, p2, s2),
+    (S3, P3, p3, s3),
+    (S4, P4, p4, s4),
+    (S5, P5, p5, s5),
+    (S6, P6, p6, s6),
+    (S7, P7, p7, s7)
+);
+impl_state_pattern_tuple!(
+    (S1, P1, p1, s1),
+    (S2, P2, p2, s2),
+    (S3, P3, p3, s3),
+    (S4, P4, p4, s4),
+    (S5, P5, p5, s5),
+    (S6, P6, p6, s6),
+    (S7, P7, p7, s7),
+    (S8, P8, p8, s8)
+);
+
+/// A pattern that matches a tuple of [`State`] types at once, where each `State` type must be
+/// in a state matching its own [`StatePattern`].
+///
+/// Enter and exit hooks run in the [`StateFlush`](crate::schedule::StateFlush) schedule when the
+/// joint condition starts or stops holding, regardless of which `State` type flushed. They run
+/// after the [`ResolveStateSystems::Resolve`] system set of every `State` type in the tuple and
+/// before [`ApplyFlushSystems`], so they see every guarded or held flush already resolved, but
+/// they aren't part of any `Resolve` system set. A flush that is skipped by the
+/// [`StateFlushFilter`] is not counted, and the sync points added by
+/// [`schedule_sync_flush`](crate::schedule::sync_flush::schedule_sync_flush) don't apply to these
+/// hooks:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::{pattern::JointPattern, prelude::*};
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # enum Screen {
+/// #     Title,
+/// #     Gameplay,
+/// # }
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Paused(bool);
+/// #
+/// # fn spawn_pause_menu() {}
+/// # fn despawn_pause_menu() {}
+/// # fn tick_gameplay() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// let playing = JointPattern::new((Screen::Gameplay, Paused(false)));
+/// app.add_systems(Update, playing.clone().on_update(tick_gameplay));
+/// app.add_systems(StateFlush, (
+///     playing.clone().on_exit(spawn_pause_menu),
+///     playing.on_enter(despawn_pause_menu),
+/// ));
+/// # }
+/// ```
+pub struct JointPattern<S, P: StatePatternTuple<S>>(P, PhantomData<S>);
+
+impl<S, P: StatePatternTuple<S> + Clone> Clone for JointPattern<S, P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<S: 'static + Send + Sync, P: StatePatternTuple<S>> JointPattern<S, P> {
+    /// Create a new `JointPattern` from a tuple of [`StatePattern`] types.
+    pub fn new(patterns: P) -> Self {
+        Self(patterns, PhantomData)
+    }
+
+    /// Build a run condition that checks if the joint condition holds.
+    pub fn will_update(
+        self,
+    ) -> impl 'static + Send + Sync + Fn(StaticSystemParam<P::Param>) -> bool {
+        move |param| self.0.is_in(&param)
+    }
+
+    /// Configure systems to run if the joint condition holds.
+    pub fn on_update<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        systems.run_if(self.will_update())
+    }
+
+    /// Build a run condition that checks if the joint condition will stop holding after any
+    /// triggered flushes are applied.
+    pub fn will_exit(
+        self,
+    ) -> impl 'static
+    + Send
+    + Sync
+    + Fn(StaticSystemParam<P::Param>, Option<Res<StateFlushFilter>>) -> bool {
+        move |param, filter| self.0.is_in(&param) && !self.0.will_be_in(&param, filter.as_deref())
+    }
+
+    /// Configure systems to run when the joint condition stops holding.
+    pub fn on_exit<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        P::after_resolve(systems.run_if(self.will_exit())).before(ApplyFlushSystems)
+    }
+
+    /// Build a run condition that checks if the joint condition will start holding after any
+    /// triggered flushes are applied.
+    pub fn will_enter(
+        self,
+    ) -> impl 'static
+    + Send
+    + Sync
+    + Fn(StaticSystemParam<P::Param>, Option<Res<StateFlushFilter>>) -> bool {
+        move |param, filter| !self.0.is_in(&param) && self.0.will_be_in(&param, filter.as_deref())
+    }
+
+    /// Configure systems to run when the joint condition starts holding.
+    pub fn on_enter<M>(
+        self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> ScheduleConfigs<ScheduleSystem> {
+        P::after_resolve(systems.run_if(self.will_enter())).before(ApplyFlushSystems)
+    }
+}
+
+/// A phase of a flush of the [`State`] type `S` that can pass a value to hook systems as
+/// [`In<Self::Input>`](In) or [`InRef<Self::Input>`](InRef).
+///
+/// Implemented for [`ExitHook`], [`TransHook`], [`EnterHook`], and [`FlushHook`] when `S`
+/// implements `Clone`.
+pub trait StateHookInput<S: State>: 'static {
+    /// The value passed to hook systems.
+    type Input: 'static + Send + Sync;
+
+    /// Read the value from the current and next states.
+    fn read(state: FlushRef<S>) -> Self::Input;
+}
+
+/// The exit phase of a flush, which passes the old state as `In<S>` or `InRef<S>`.
+pub struct ExitHook;
+
+impl<S: State + Clone> StateHookInput<S> for ExitHook {
+    type Input = S;
+
+    fn read(state: FlushRef<S>) -> S {
+        state.current.unwrap().clone()
+    }
+}
+
+/// The transition phase of a flush, which passes the old and new states as `In<(S, S)>` or
+/// `InRef<(S, S)>`.
+pub struct TransHook;
+
+impl<S: State + Clone> StateHookInput<S> for TransHook {
+    type Input = (S, S);
+
+    fn read(state: FlushRef<S>) -> (S, S) {
+        let (old, new) = state.unwrap();
+        (old.clone(), new.clone())
+    }
+}
+
+/// The enter phase of a flush, which passes the new state as `In<S>` or `InRef<S>`.
+pub struct EnterHook;
+
+impl<S: State + Clone> StateHookInput<S> for EnterHook {
+    type Input = S;
+
+    fn read(state: FlushRef<S>) -> S {
+        state.next.unwrap().clone()
+    }
+}
+
+/// Any phase of a flush that may enable or disable `S`, which passes the old and new states as
+/// `In<(Option<S>, Option<S>)>` or `InRef<(Option<S>, Option<S>)>`.
+pub struct FlushHook;
+
+impl<S: State + Clone> StateHookInput<S> for FlushHook {
+    type Input = (Option<S>, Option<S>);
+
+    fn read(state: FlushRef<S>) -> (Option<S>, Option<S>) {
+        let (old, new) = state.get();
+        (old.cloned(), new.cloned())
+    }
+}
+
+/// Systems that can be configured as hooks for the [`State`] type `S` in the flush phase `H`.
+///
+/// Implemented for any [`IntoScheduleConfigs`] type, and for any system that takes
+/// [`In<H::Input>`](StateHookInput::Input) or [`InRef<H::Input>`](StateHookInput::Input) as
+/// input.
+///
+/// A hook system that takes `InRef` runs as an exclusive system. The input is cloned once per
+/// run and then lent to the hook system, which avoids moving the value into the system.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+/// # struct Level(usize);
+/// #
+/// fn spawn_level(In(level): In<Level>) {
+///     info!("Spawning level {}", level.0);
+/// }
+///
+/// fn log_level_up(In((old, new)): In<(Level, Level)>) {
+///     info!("Level up: {old:?} -> {new:?}");
+/// }
+///
+/// fn log_level_exit(InRef(level): InRef<Level>) {
+///     info!("Exiting level {}", level.0);
+/// }
+///
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, (
+///     Level::ANY.on_enter(spawn_level),
+///     Level::when(|x, y| y.0 > x.0).on_trans(log_level_up),
+///     Level::ANY.on_exit(log_level_exit),
+/// ));
+/// # }
+/// ```
+pub trait IntoStateHookConfigs<S, H, M> {
+    /// Convert into hook system configs.
+    fn into_hook_configs(self) -> ScheduleConfigs<ScheduleSystem>;
+}
+
+/// A marker for hook systems that take no input.
+pub struct WithoutHookInput;
+
+/// A marker for hook systems that take [`In<H::Input>`](StateHookInput::Input) as input.
+pub struct WithHookInput;
+
+impl<S, H, M, T> IntoStateHookConfigs<S, H, (WithoutHookInput, M)> for T
+where
+    T: IntoScheduleConfigs<ScheduleSystem, M>,
+{
+    fn into_hook_configs(self) -> ScheduleConfigs<ScheduleSystem> {
+        self.into_configs()
+    }
+}
+
+impl<S: State, H: StateHookInput<S>, M, T> IntoStateHookConfigs<S, H, (WithHookInput, M)> for T
+where
+    T: IntoSystem<In<H::Input>, (), M>,
+{
+    fn into_hook_configs(self) -> ScheduleConfigs<ScheduleSystem> {
+        H::read.pipe(self).into_configs()
+    }
+}
+
+/// A marker for hook systems that take [`InRef<H::Input>`](StateHookInput::Input) as input.
+pub struct WithHookInputRef;
+
+impl<S: State, H: StateHookInput<S>, M, T> IntoStateHookConfigs<S, H, (WithHookInputRef, M)> for T
+where
+    T: 'static + Send + Sync + IntoSystem<InRef<'static, H::Input>, (), M>,
+{
+    fn into_hook_configs(self) -> ScheduleConfigs<ScheduleSystem> {
+        let mut system = Some(self);
+        (move |world: &mut World,
+               mut id: Local<Option<SystemId<InRef<'static, H::Input>>>>|
+              -> Result {
+            let id = *id.get_or_insert_with(|| world.register_system(system.take().unwrap()));
+            let input = world.run_system_cached(H::read)?;
+            match world.run_system_with(id, &input) {
+                Err(RegisteredSystemError::Skipped(_)) => Ok(()),
+                result => Ok(result?),
+            }
+        })
+        .into_configs()
+    }
+}
+
+/// A macro for building pattern-matching [`FnStatePattern`], [`FnStateTransPattern`], and
+/// [`FnStateFlushPattern`] instances.
+///
+/// # Examples
+///
+/// State pattern-matching:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn save_checkpoint() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, state!(Level(4 | 7 | 10)).on_enter(save_checkpoint));
+/// # }
+/// ```
+///
+/// State transition pattern-matching:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn do_something_cool() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, state!(Level(x @ 1..=3) => y if y.0 == 10 - x).on_trans(do_something_cool));
+/// # }
+/// ```
+///
+/// State flush pattern-matching, where `None` is a disabled state:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::prelude::*;
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn play_intro_cutscene() {}
+/// # fn show_credits() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// app.add_systems(StateFlush, state!(None => Level(1)).on_enter(play_intro_cutscene));
+/// app.add_systems(StateFlush, state!(Some(Level(10)) => None).on_exit(show_credits));
+/// # }
+/// ```
+///
+/// `state!(None => None)` matches a flush that is triggered while the state is disabled and
+/// leaves it disabled. The pattern doesn't name the state type, so it must be annotated:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use pyri_state::{pattern::FnStateFlushPattern, prelude::*};
+/// #
+/// # #[derive(State, Resource, Clone, PartialEq, Eq)]
+/// # struct Level(usize);
+/// #
+/// # fn log_level_still_disabled() {}
+/// #
+/// # fn plugin(app: &mut App) {
+/// let still_disabled: FnStateFlushPattern<Level, _> = state!(None => None);
+/// app.add_systems(StateFlush, still_disabled.on_trans(log_level_still_disabled));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! state {
+    ($state:pat $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStatePattern::new(
+            |state| matches!(state, $state $(if $guard)?),
+        )
+    };
+
+    (None => None $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateFlushPattern::new(
+            |old, new| matches!((old, new), (None, None) $(if $guard)?),
+        )
+    };
+
+    (None => Some($new:pat) $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateFlushPattern::new(
+            |old, new| matches!((old, new), (None, Some($new)) $(if $guard)?),
+        )
+    };
+
+    (None => $new:pat $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateFlushPattern::new(
+            |old, new| matches!((old, new), (None, Some($new)) $(if $guard)?),
+        )
+    };
+
+    (Some($old:pat) => None $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateFlushPattern::new(
+            |old, new| matches!((old, new), (Some($old), None) $(if $guard)?),
+        )
+    };
+
+    ($old:pat => None $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateFlushPattern::new(
+            |old, new| matches!((old, new), (Some($old), None) $(if $guard)?),
+        )
+    };
+
+    ($old:pat => $new:pat $(if $guard:expr)? $(,)?) => {
+        pyri_state::pattern::FnStateTransPattern::new(
+            |old, new| matches!((old, new), ($old, $new) $(if $guard)?),
+        )
+    };
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use bevy_app::App;
+    use bevy_ecs::{resource::Resource, system::ResMut};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    struct Level(usize);
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<String>);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_resource::<Log>()
+            .add_state::<Level>();
+        app
+    }
+
+    fn log(app: &mut App) -> Vec<String> {
+        let mut log = core::mem::take(&mut app.world_mut().resource_mut::<Log>().0);
+        log.sort();
+        log
+    }
+
+    #[test]
+    fn hooks_take_state_by_ref() {
+        let mut app = app();
+        app.add_systems(
+            StateFlush,
+            (
+                Level::ANY.on_exit(|InRef(x): InRef<Level>, mut log: ResMut<Log>| {
+                    log.0.push(alloc::format!("exit {}", x.0));
+                }),
+                Level::ANY_TO_ANY.on_trans(
+                    |InRef((x, y)): InRef<(Level, Level)>, mut log: ResMut<Log>| {
+                        log.0.push(alloc::format!("trans {} {}", x.0, y.0));
+                    },
+                ),
+                Level::ANY.on_enter(|InRef(x): InRef<Level>, mut log: ResMut<Log>| {
+                    log.0.push(alloc::format!("enter {}", x.0));
+                }),
+                Level::ANY.on_enable(|InRef(x): InRef<Level>, mut log: ResMut<Log>| {
+                    log.0.push(alloc::format!("enable {}", x.0));
+                }),
+                Level::ANY.on_disable(|In(x): In<Level>, mut log: ResMut<Log>| {
+                    log.0.push(alloc::format!("disable {}", x.0));
+                }),
+            ),
+        );
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(log(&mut app), ["enable 1", "enter 1"]);
+
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert_eq!(log(&mut app), ["enter 2", "exit 1", "trans 1 2"]);
+
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert_eq!(log(&mut app), ["disable 2", "exit 2"]);
+    }
+
+    #[test]
+    fn flush_hooks_take_optional_states() {
+        let mut app = app();
+        app.add_systems(
+            StateFlush,
+            (
+                Level::when_flush(|_, _| true).on_trans(
+                    |In((x, y)): In<(Option<Level>, Option<Level>)>, mut log: ResMut<Log>| {
+                        log.0.push(alloc::format!("trans {:?} {:?}", x, y));
+                    },
+                ),
+                Level::when_flush(|x, _| x.is_none()).on_enter(
+                    |InRef((x, y)): InRef<(Option<Level>, Option<Level>)>, mut log: ResMut<Log>| {
+                        log.0.push(alloc::format!("enter {:?} {:?}", x, y));
+                    },
+                ),
+            ),
+        );
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(
+            log(&mut app),
+            ["enter None Some(Level(1))", "trans None Some(Level(1))"],
+        );
+
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert_eq!(log(&mut app), ["trans Some(Level(1)) None"]);
+    }
+
+    #[test]
+    fn combinators_stay_within_their_pattern_kind() {
+        let mut app = app();
+        app.add_systems(
+            StateFlush,
+            (
+                (Level::ANY & !state!(Level(2)) | Level(3)).on_enter(
+                    |InRef(x): InRef<Level>, mut log: ResMut<Log>| {
+                        log.0.push(alloc::format!("enter {}", x.0));
+                    },
+                ),
+                (Level::ANY_TO_ANY & !Level::when(|x, y| y.0 < x.0)).on_trans(
+                    |InRef((x, y)): InRef<(Level, Level)>, mut log: ResMut<Log>| {
+                        log.0.push(alloc::format!("trans {} {}", x.0, y.0));
+                    },
+                ),
+                (state!(None => _) | state!(_ => None))
+                    .and(Level::when_flush(|x, y| x.or(y).is_some_and(|x| x.0 != 3)))
+                    .on_exit(
+                        |InRef((x, y)): InRef<(Option<Level>, Option<Level>)>,
+                         mut log: ResMut<Log>| {
+                            log.0.push(alloc::format!("toggle {:?} {:?}", x, y));
+                        },
+                    ),
+            ),
+        );
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(log(&mut app), ["enter 1", "toggle None Some(Level(1))"]);
+
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert_eq!(log(&mut app), ["trans 1 2"]);
+
+        app.world_mut().set_next_state(Some(Level(3)));
+        app.update();
+        assert_eq!(log(&mut app), ["enter 3", "trans 2 3"]);
+
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert_eq!(log(&mut app), ["toggle Some(Level(2)) None"]);
+    }
+
+    #[test]
+    fn none_to_none_matches_flush_while_disabled() {
+        let mut app = app();
+        let still_disabled: FnStateFlushPattern<Level, _> = state!(None => None);
+        app.add_systems(
+            StateFlush,
+            still_disabled.on_trans(|mut log: ResMut<Log>| log.0.push("still disabled".into())),
+        );
+
+        app.world_mut().trigger_state::<Level>();
+        app.update();
+        assert_eq!(log(&mut app), ["still disabled"]);
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert!(log(&mut app).is_empty());
+
+        app.update();
+        assert!(log(&mut app).is_empty());
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    struct Paused(bool);
+
+    fn joint_app() -> App {
+        let mut app = app();
+        app.add_state::<Paused>();
+        let playing = JointPattern::new((Level::ANY, Paused(false)));
+        app.add_systems(
+            StateFlush,
+            (
+                playing
+                    .clone()
+                    .on_exit(|mut log: ResMut<Log>| log.0.push("exit".into())),
+                playing.on_enter(|mut log: ResMut<Log>| log.0.push("enter".into())),
+            ),
+        );
+        app
+    }
+
+    #[test]
+    fn joint_hooks_run_when_the_joint_condition_changes() {
+        let mut app = joint_app();
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().set_next_state(Some(Paused(false)));
+        app.update();
+        assert_eq!(log(&mut app), ["enter"]);
+
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().set_next_state(Some(Paused(true)));
+        app.update();
+        assert_eq!(log(&mut app), ["exit"]);
+    }
+
+    #[test]
+    fn joint_hooks_ignore_filtered_flushes() {
+        let mut app = joint_app();
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.world_mut().set_next_state(Some(Paused(false)));
+        app.world_mut().trigger_state::<Paused>();
+
+        // Only `Level` flushes, so `Paused` is still disabled.
+        app.world_mut().flush_state::<Level>();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().flush_state::<Paused>();
+        assert_eq!(log(&mut app), ["enter"]);
+
+        app.world_mut().set_next_state(Some(Paused(true)));
+        app.world_mut().trigger_state::<Paused>();
+        app.world_mut().flush_state::<Level>();
+        assert!(log(&mut app).is_empty());
+
+        app.world_mut().flush_all_states();
+        assert_eq!(log(&mut app), ["exit"]);
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Rank(u8);
+
+    impl From<u8> for Rank {
+        fn from(value: u8) -> Self {
+            Self(value)
+        }
+    }
+
+    #[test]
+    fn range_patterns_accept_convertible_bounds() {
+        let ranks = (0..10).map(Rank).collect::<Vec<_>>();
+        let matching = |pattern: RangeStatePattern<Rank>| {
+            ranks
+                .iter()
+                .filter(|x| pattern.matches(x))
+                .map(|x| x.0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching(Rank::at_least(7)), [7, 8, 9]);
+        assert_eq!(matching(Rank::between(Rank(3), 5)), [3, 4, 5]);
+        assert_eq!(matching(Rank::below(2)), [0, 1]);
+    }
 }
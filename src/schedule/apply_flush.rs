@@ -9,19 +9,19 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
     /// A plugin that adds an apply flush system for the [`State`] type `S`
-    /// to the [`StateFlush`] schedule.
+    /// to the [`StateFlush`](crate::schedule::StateFlush) schedule.
     ///
     /// Calls [`schedule_apply_flush<S>`].
     pub struct ApplyFlushPlugin<S: State + Clone>(PhantomData<S>);
 
     impl<S: State + Clone> Plugin for ApplyFlushPlugin<S> {
         fn build(&self, app: &mut App) {
-            schedule_apply_flush::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_apply_flush::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
@@ -35,12 +35,13 @@ mod app {
 use core::{fmt::Debug, hash::Hash};
 
 use bevy_ecs::{
-    schedule::{IntoScheduleConfigs as _, Schedule, SystemSet},
+    schedule::{IntoScheduleConfigs as _, Schedule, SystemCondition as _, SystemSet},
     system::Commands,
 };
 
 use crate::{
     access::{CurrentMut, NextRef},
+    schedule::is_flush_allowed,
     state::State,
 };
 
@@ -72,7 +73,7 @@ fn apply_flush<S: State + Clone>(
 pub fn schedule_apply_flush<S: State + Clone>(schedule: &mut Schedule) {
     schedule.add_systems(
         (apply_flush::<S>, S::reset_trigger)
-            .run_if(S::is_triggered)
+            .run_if(S::is_triggered.and_then(is_flush_allowed::<S>))
             .in_set(ApplyFlushSystems),
     );
 }
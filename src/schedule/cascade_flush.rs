@@ -0,0 +1,247 @@
+//! Re-run the [`StateFlush`] schedule until no state is triggered to flush.
+//!
+//! Enable this with [`StateFlushPlugin::cascade`](crate::setup::StateFlushPlugin::cascade).
+
+use core::{fmt::Debug, hash::Hash};
+
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{
+        IntoScheduleConfigs as _, Schedule, ScheduleLabel, SystemSet,
+        common_conditions::resource_exists,
+    },
+    system::{Res, ResMut},
+    world::World,
+};
+use tiny_bail::prelude::*;
+
+use crate::{
+    next_state::TriggerStateFlush,
+    schedule::{ApplyFlushSystems, StateFlush},
+    state::State,
+};
+
+/// The schedule that re-runs [`StateFlush`] until no [`State`] type is triggered to flush,
+/// added after `StateFlush` by [`StateFlushPlugin::cascade`](crate::setup::StateFlushPlugin::cascade).
+#[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct CascadeStateFlush;
+
+/// A system set that checks for pending [`State`] flushes after [`ApplyFlushSystems`]
+/// in the [`StateFlush`] schedule.
+#[derive(SystemSet, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct CascadeFlushSystems;
+
+/// A resource that configures the [`CascadeStateFlush`] schedule.
+///
+/// Inserted by [`StateFlushPlugin::cascade`](crate::setup::StateFlushPlugin::cascade).
+#[derive(Resource, Debug)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct StateFlushCascade {
+    /// The maximum number of times [`StateFlush`] can run in a row before giving up.
+    pub max_iterations: usize,
+    pending: bool,
+    limit_reached: bool,
+}
+
+impl StateFlushCascade {
+    /// Create a new `StateFlushCascade` with a maximum number of iterations.
+    pub fn new(max_iterations: usize) -> Self {
+        Self {
+            max_iterations,
+            pending: false,
+            limit_reached: false,
+        }
+    }
+
+    /// Check if any [`State`] type was still triggered to flush at the end of the
+    /// last [`StateFlush`] run.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Check if the last run of [`CascadeStateFlush`] gave up after
+    /// [`max_iterations`](Self::max_iterations) runs of [`StateFlush`], deferring the
+    /// remaining state flushes to the next run.
+    pub fn is_limit_reached(&self) -> bool {
+        self.limit_reached
+    }
+}
+
+/// Re-run [`StateFlush`] until no [`State`] type is triggered to flush, up to
+/// [`StateFlushCascade::max_iterations`] runs in total.
+///
+/// Added to the [`CascadeStateFlush`] schedule by
+/// [`StateFlushPlugin::cascade`](crate::setup::StateFlushPlugin::cascade).
+pub fn cascade_state_flush(world: &mut World) {
+    let mut cascade = r!(world.get_resource_mut::<StateFlushCascade>());
+    cascade.limit_reached = false;
+    let max_iterations = cascade.max_iterations;
+
+    // `StateFlush` already ran once.
+    for _ in 1..max_iterations {
+        let mut cascade = r!(world.get_resource_mut::<StateFlushCascade>());
+        if !cascade.pending {
+            return;
+        }
+        cascade.pending = false;
+        world.run_schedule(StateFlush);
+    }
+
+    let mut cascade = r!(world.get_resource_mut::<StateFlushCascade>());
+    if cascade.pending {
+        cascade.pending = false;
+        cascade.limit_reached = true;
+        bevy_log::warn!(
+            "{StateFlush:?} did not settle after {max_iterations} iterations. \
+            Remaining state flushes will be deferred to the next run.",
+        );
+    }
+}
+
+/// Record whether the [`State`] type `S` is still triggered to flush at the end of a
+/// [`StateFlush`] run.
+///
+/// Added to [`CascadeFlushSystems`] by [`schedule_cascade_flush<S>`].
+pub fn mark_pending<S: State>(
+    trigger: Res<TriggerStateFlush<S>>,
+    mut cascade: ResMut<StateFlushCascade>,
+) {
+    cascade.pending |= trigger.0;
+}
+
+/// Add a pending flush check for the [`State`] type `S` to a schedule.
+///
+/// Used in [`ResolveStatePlugin<S>`](crate::schedule::resolve_state::ResolveStatePlugin).
+pub fn schedule_cascade_flush<S: State>(schedule: &mut Schedule) {
+    schedule.configure_sets(CascadeFlushSystems.after(ApplyFlushSystems));
+    schedule.add_systems(
+        mark_pending::<S>
+            .run_if(resource_exists::<StateFlushCascade>)
+            .in_set(CascadeFlushSystems),
+    );
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::{resource::Resource, schedule::IntoScheduleConfigs as _, system::ResMut};
+
+    use crate::{
+        next_state::TriggerStateFlush, prelude::*, schedule::ResolveStateSystems,
+        setup::WorldExtState as _,
+    };
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    enum Leader {
+        #[default]
+        A,
+        B,
+    }
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(before(Leader))]
+    struct Follower(usize);
+
+    #[derive(Resource, Default)]
+    struct Runs(usize);
+
+    fn app(cascade: Option<usize>) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin);
+        if let Some(max_iterations) = cascade {
+            app.add_plugins(StateFlushPlugin::default().cascade(max_iterations));
+        }
+        app.init_state::<Leader>()
+            .init_state::<Follower>()
+            .init_resource::<Runs>()
+            .add_systems(StateFlush, |mut runs: ResMut<Runs>| runs.0 += 1);
+        app.update();
+        app.world_mut().resource_mut::<Runs>().0 = 0;
+        app
+    }
+
+    fn follow_leader(app: &mut App) {
+        app.add_systems(
+            StateFlush,
+            Leader::B.on_enter(|mut follower: NextMut<Follower>| follower.enter(Follower(1))),
+        );
+    }
+
+    #[test]
+    fn dependent_state_settles_in_one_frame() {
+        let mut app = app(Some(4));
+        follow_leader(&mut app);
+
+        app.world_mut().set_next_state(Some(Leader::B));
+        app.update();
+        assert_eq!(app.world().state::<Leader>(), Some(&Leader::B));
+        assert_eq!(app.world().state::<Follower>(), Some(&Follower(1)));
+        assert_eq!(app.world().resource::<Runs>().0, 2);
+        assert!(
+            !app.world()
+                .resource::<StateFlushCascade>()
+                .is_limit_reached()
+        );
+    }
+
+    #[test]
+    fn dependent_state_lags_without_cascade() {
+        let mut app = app(None);
+        follow_leader(&mut app);
+
+        app.world_mut().set_next_state(Some(Leader::B));
+        app.update();
+        assert_eq!(app.world().state::<Follower>(), Some(&Follower(0)));
+        app.update();
+        assert_eq!(app.world().state::<Follower>(), Some(&Follower(1)));
+    }
+
+    #[test]
+    fn endless_cascade_stops_at_limit() {
+        let mut app = app(Some(4));
+        app.add_systems(
+            StateFlush,
+            (|current: CurrentRef<Leader>, mut next: NextMut<Leader>| {
+                next.enter(match current.unwrap() {
+                    Leader::A => Leader::B,
+                    Leader::B => Leader::A,
+                })
+            })
+            .in_set(ResolveStateSystems::<Leader>::Applied),
+        );
+
+        app.world_mut().set_next_state(Some(Leader::B));
+        app.update();
+        assert_eq!(app.world().resource::<Runs>().0, 4);
+        assert!(
+            app.world()
+                .resource::<StateFlushCascade>()
+                .is_limit_reached()
+        );
+    }
+
+    #[test]
+    fn held_flush_ends_cascade() {
+        let mut app = app(Some(4));
+        app.add_systems(
+            StateFlush,
+            (|mut trigger: ResMut<TriggerStateFlush<Follower>>| trigger.0 = false)
+                .in_set(ResolveStateSystems::<Follower>::Guard),
+        );
+
+        app.world_mut().set_next_state(Some(Follower(1)));
+        app.update();
+        app.update();
+        assert_eq!(app.world().state::<Follower>(), Some(&Follower(0)));
+        assert_eq!(app.world().resource::<Runs>().0, 2);
+        assert!(
+            !app.world()
+                .resource::<StateFlushCascade>()
+                .is_limit_reached()
+        );
+    }
+}
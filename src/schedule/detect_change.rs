@@ -9,19 +9,19 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
     /// A plugin that adds a change detection system for the [`State`] type `S`
-    /// to the [`StateFlush`] schedule.
+    /// to the [`StateFlush`](crate::schedule::StateFlush) schedule.
     ///
     /// Calls [`schedule_detect_change<S>`].
     pub struct DetectChangePlugin<S: State + Eq>(PhantomData<S>);
 
     impl<S: State + Eq> Plugin for DetectChangePlugin<S> {
         fn build(&self, app: &mut App) {
-            schedule_detect_change::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_detect_change::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
@@ -32,22 +32,55 @@ mod app {
     }
 }
 
-use bevy_ecs::schedule::{
-    IntoScheduleConfigs as _, Schedule, SystemCondition, common_conditions::not,
+use bevy_ecs::{
+    change_detection::DetectChanges as _,
+    schedule::{
+        IntoScheduleConfigs as _, Schedule, SystemCondition,
+        common_conditions::{not, resource_exists},
+    },
+    system::{Res, SystemChangeTick},
 };
 
 use crate::{
-    schedule::resolve_state::ResolveStateSystems,
+    next_state::AppliedStateFlush,
+    schedule::{
+        cascade_flush::{CascadeFlushSystems, StateFlushCascade, mark_pending},
+        resolve_state::ResolveStateSystems,
+    },
     state::{State, StateExtEq as _},
 };
 
+// Check if the next state was changed after `S` resolved in the latest run of its flush schedule.
+// A flush that was held or rejected by a guard in `ResolveStateSystems::<S>::Guard` will not count.
+fn was_changed_after_resolve<S: State>(
+    next: Res<S::Next>,
+    applied: Res<AppliedStateFlush<S>>,
+    tick: SystemChangeTick,
+) -> bool {
+    next.last_changed()
+        .is_newer_than(applied.last_changed(), tick.this_run())
+}
+
 /// Add change detection systems for the [`State`] type `S` to a schedule.
 ///
+/// If [cascading](crate::setup::StateFlushPlugin::cascade) is enabled, changes made after `S`
+/// has resolved will also be detected in [`CascadeFlushSystems`] so that `S` can flush again in
+/// the same frame.
+///
 /// Used in [`DetectChangePlugin<S>`].
 pub fn schedule_detect_change<S: State + Eq>(schedule: &mut Schedule) {
-    schedule.add_systems(
+    schedule.add_systems((
         S::trigger
             .run_if(not(S::is_triggered).and_then(S::will_change))
             .in_set(ResolveStateSystems::<S>::Trigger),
-    );
+        S::trigger
+            .run_if(
+                resource_exists::<StateFlushCascade>
+                    .and_then(was_changed_after_resolve::<S>)
+                    .and_then(not(S::is_triggered))
+                    .and_then(S::will_change),
+            )
+            .in_set(CascadeFlushSystems)
+            .before(mark_pending::<S>),
+    ));
 }
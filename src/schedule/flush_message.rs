@@ -9,12 +9,12 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::flush_schedule_mut;
 
     use super::*;
 
     /// A plugin that adds a [`StateFlushMessage<S>`] writing system for the [`State`] type `S`
-    /// to the [`StateFlush`] schedule.
+    /// to the [`StateFlush`](crate::schedule::StateFlush) schedule.
     ///
     /// Calls [`schedule_flush_message<S>`].
     pub struct FlushMessagePlugin<S: State + Clone>(PhantomData<S>);
@@ -22,7 +22,7 @@ mod app {
     impl<S: State + Clone> Plugin for FlushMessagePlugin<S> {
         fn build(&self, app: &mut App) {
             app.add_message::<StateFlushMessage<S>>();
-            schedule_flush_message::<S>(app.get_schedule_mut(StateFlush).unwrap());
+            schedule_flush_message::<S>(flush_schedule_mut::<S>(app));
         }
     }
 
@@ -45,6 +45,7 @@ use crate::{access::FlushRef, schedule::ResolveStateSystems, state::State};
 /// Added [by default](pyri_state_derive::State) by [`FlushMessagePlugin<S>`].
 #[derive(Message)]
 #[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StateFlushMessage<S: State> {
     /// The state before the flush, or `None` if disabled.
     pub old: Option<S>,
@@ -0,0 +1,189 @@
+//! Reject a pending state flush with guard systems and write a [`StateFlushRejected`] message.
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use super::*;
+
+    /// A plugin that adds the [`StateFlushRejected<S>`] message for the [`State`] type `S`.
+    ///
+    /// Added by the `guard_flush` [derive macro option](pyri_state_derive::State), which is
+    /// implied by `transitions(...)`. Required by [`guard_flush<S>`] systems.
+    pub struct GuardFlushPlugin<S: State + Clone>(PhantomData<S>);
+
+    impl<S: State + Clone> Plugin for GuardFlushPlugin<S> {
+        fn build(&self, app: &mut App) {
+            app.add_message::<StateFlushRejected<S>>();
+        }
+    }
+
+    impl<S: State + Clone> Default for GuardFlushPlugin<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+}
+
+use alloc::string::{String, ToString as _};
+
+use bevy_ecs::{
+    message::{Message, MessageWriter},
+    schedule::{IntoScheduleConfigs as _, ScheduleConfigs},
+    system::{In, IntoSystem, ScheduleSystem, System as _},
+};
+
+use crate::{
+    access::FlushMut,
+    schedule::ResolveStateSystems,
+    state::{State, StateMut},
+};
+
+/// A message written whenever a guard rejects a flush of the [`State`] type `S`.
+///
+/// Added by [`GuardFlushPlugin<S>`].
+#[derive(Message)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct StateFlushRejected<S: State> {
+    /// The state before the rejected flush, or `None` if disabled.
+    pub old: Option<S>,
+    /// The rejected next state, or `None` if disabled.
+    pub new: Option<S>,
+    /// The name of the guard system that rejected the flush.
+    pub guard: String,
+}
+
+/// Build a guard system for the [`State`] type `S` from a `check` system.
+///
+/// If `check` returns `false`, the pending flush will be rejected: the trigger will be reset,
+/// the next state will be set back to the current state, and a [`StateFlushRejected<S>`]
+/// message will be written.
+///
+/// Requires [`GuardFlushPlugin<S>`] (e.g. via the `guard_flush`
+/// [derive macro option](pyri_state_derive::State)).
+///
+/// The guard system runs in [`ResolveStateSystems::<S>::Guard`] if `S` is triggered.
+/// See [`StateTransPattern::guard`](crate::pattern::StateTransPattern::guard) for the usual
+/// way to add a guard.
+pub fn guard_flush<S: StateMut + Clone, M>(
+    check: impl IntoSystem<(), bool, M>,
+) -> ScheduleConfigs<ScheduleSystem> {
+    let check = IntoSystem::into_system(check);
+    let guard = check.name().to_string();
+
+    check
+        .pipe(
+            move |pass: In<bool>,
+                  mut state: FlushMut<S>,
+                  mut message: MessageWriter<StateFlushRejected<S>>| {
+                if *pass {
+                    return;
+                }
+
+                let (old, new) = state.get();
+                message.write(StateFlushRejected {
+                    old: old.cloned(),
+                    new: new.cloned(),
+                    guard: guard.clone(),
+                });
+                state.reset();
+            },
+        )
+        .run_if(S::is_triggered)
+        .in_set(ResolveStateSystems::<S>::Guard)
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::App;
+    use bevy_ecs::{message::Messages, resource::Resource, system::Res};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    #[state(guard_flush)]
+    struct Level(usize);
+
+    fn app(guards: ScheduleConfigs<ScheduleSystem>) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .add_state::<Level>()
+            .add_systems(StateFlush, guards);
+        app
+    }
+
+    fn rejections(app: &mut App) -> usize {
+        app.world_mut()
+            .resource_mut::<Messages<StateFlushRejected<Level>>>()
+            .drain()
+            .count()
+    }
+
+    #[test]
+    fn trans_guard_does_not_reject_enable_or_disable() {
+        let mut app = app(Level::ANY_TO_ANY.guard(|| false));
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(1)));
+
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(1)));
+        assert_eq!(rejections(&mut app), 1);
+
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), None);
+    }
+
+    #[test]
+    fn enable_and_disable_guards_reject() {
+        #[derive(Resource)]
+        struct Locked(bool);
+
+        let mut app = app((
+            Level::ANY.guard_enable(|locked: Res<Locked>| !locked.0),
+            Level::ANY.guard_disable(|locked: Res<Locked>| !locked.0),
+        )
+            .into_configs());
+        app.insert_resource(Locked(true));
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), None);
+        assert_eq!(rejections(&mut app), 1);
+
+        app.world_mut().resource_mut::<Locked>().0 = false;
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(1)));
+
+        app.world_mut().resource_mut::<Locked>().0 = true;
+        app.world_mut().set_next_state::<Level>(None);
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(1)));
+        assert_eq!(rejections(&mut app), 1);
+    }
+
+    #[test]
+    fn flush_guard_rejects_any_flush() {
+        let mut app = app(Level::when_flush(|_, _| true).guard(|| false));
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), None);
+        assert_eq!(
+            app.world_mut().next_state::<Level>(),
+            None,
+            "a rejected flush resets the next state",
+        );
+        assert_eq!(rejections(&mut app), 1);
+    }
+}
@@ -1,24 +1,141 @@
 //! State flush scheduling types and functions.
 //!
-//! The [`StateFlush`] schedule handles all [`State`](crate::state::State) flush logic
+//! The [`StateFlush`] schedule handles all [`State`] flush logic
 //! and emits [`StateFlushMessage`](flush_message::StateFlushMessage).
 
 pub use apply_flush::ApplyFlushSystems;
 pub use resolve_state::ResolveStateSystems;
 
 pub mod apply_flush;
+pub mod cascade_flush;
 pub mod detect_change;
 pub mod flush_message;
+pub mod guard_flush;
+pub mod observe_flush;
 pub mod resolve_state;
+pub mod sync_flush;
+pub mod validate_trans;
 
-use core::{fmt::Debug, hash::Hash};
+#[cfg(feature = "bevy_app")]
+pub use app::*;
 
-use bevy_ecs::schedule::ScheduleLabel;
+#[cfg(feature = "bevy_app")]
+mod app {
+    use bevy_app::App;
+    use bevy_ecs::schedule::Schedule;
 
-/// The schedule that handles all [`State`](crate::state::State) flush logic, added before
+    use super::*;
+
+    /// Get the schedule that the [`State`] type `S` flushes in, initializing it if needed.
+    ///
+    /// This is [`StateFlush`] unless overridden by a [`StateFlushSchedule<S>`] resource.
+    pub fn flush_schedule_mut<S: State>(app: &mut App) -> &mut Schedule {
+        let label = flush_schedule_label::<S>(app.world());
+        app.init_schedule(label).get_schedule_mut(label).unwrap()
+    }
+}
+
+use alloc::vec::Vec;
+use core::{any::TypeId, fmt::Debug, hash::Hash, marker::PhantomData};
+
+use bevy_ecs::{
+    resource::Resource,
+    schedule::{InternedScheduleLabel, ScheduleLabel},
+    system::Res,
+    world::World,
+};
+
+use crate::state::State;
+
+/// The schedule that handles all [`State`] flush logic, added before
 /// [`PreUpdate`](bevy_app::PreUpdate) by [`StatePlugin`](crate::setup::StatePlugin).
 ///
 /// State flush hooks run in [`ResolveStateSystems::<S>::Flush`] and the flush is applied in
 /// [`ApplyFlushSystems`].
 #[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
 pub struct StateFlush;
+
+/// Get the label of the schedule that the [`State`] type `S` flushes in.
+///
+/// This is [`StateFlush`] unless overridden by a [`StateFlushSchedule<S>`] resource.
+pub fn flush_schedule_label<S: State>(world: &World) -> InternedScheduleLabel {
+    world
+        .get_resource::<StateFlushSchedule<S>>()
+        .map_or_else(|| StateFlush.intern(), |x| x.label())
+}
+
+/// A resource that overrides the schedule that the [`State`] type `S` flushes in, instead of
+/// [`StateFlush`].
+///
+/// Inserted by the `flush_in(...)` [derive macro option](pyri_state_derive::State). This must
+/// be inserted before `S` is registered, and the schedule must be run by the app (e.g. by adding
+/// it to [`MainScheduleOrder`](bevy_app::MainScheduleOrder)).
+///
+/// System ordering does not apply across schedules, so `State` types that depend on each other
+/// (via `after(...)`, `before(...)`, `substate_of(...)`, or `computed_from(...)`) must flush in the
+/// same schedule. Substates and computed states will panic if their already-registered parent or
+/// source states flush in a different schedule.
+#[derive(Resource, Debug)]
+pub struct StateFlushSchedule<S: State>(InternedScheduleLabel, PhantomData<S>);
+
+impl<S: State> StateFlushSchedule<S> {
+    /// Create a new `StateFlushSchedule` from a schedule label.
+    pub fn new(label: impl ScheduleLabel) -> Self {
+        Self(label.intern(), PhantomData)
+    }
+
+    /// Get the schedule label.
+    pub fn label(&self) -> InternedScheduleLabel {
+        self.0
+    }
+}
+
+/// A resource that lists the schedules other than [`StateFlush`] that [`State`] types flush in.
+///
+/// Updated by [`ResolveStatePlugin<S>`](resolve_state::ResolveStatePlugin) for each `State` type
+/// with a [`StateFlushSchedule<S>`], and used by
+/// [`WorldExtState::flush_all_states`](crate::setup::WorldExtState::flush_all_states).
+#[derive(Resource, Default, Debug)]
+pub struct StateFlushSchedules(Vec<InternedScheduleLabel>);
+
+impl StateFlushSchedules {
+    /// Add a schedule label if it's not [`StateFlush`] or already listed.
+    pub fn add(&mut self, label: InternedScheduleLabel) {
+        if label != StateFlush.intern() && !self.0.contains(&label) {
+            self.0.push(label);
+        }
+    }
+
+    /// Get the listed schedule labels.
+    pub fn labels(&self) -> &[InternedScheduleLabel] {
+        &self.0
+    }
+}
+
+/// A resource that restricts a run of the [`StateFlush`] schedule to a single [`State`] type.
+///
+/// While this resource exists, the [`ResolveStateSystems`] and apply flush systems of every other
+/// state type will be skipped, so their triggered flushes will remain pending.
+///
+/// Inserted temporarily by
+/// [`WorldExtState::flush_state`](crate::setup::WorldExtState::flush_state).
+#[derive(Resource, Debug)]
+pub struct StateFlushFilter(TypeId);
+
+impl StateFlushFilter {
+    /// Create a new `StateFlushFilter` that only allows the state type `S` to flush.
+    pub fn new<S: 'static>() -> Self {
+        Self(TypeId::of::<S>())
+    }
+
+    /// Check if the state type `S` is allowed to flush.
+    pub fn allows<S: 'static>(&self) -> bool {
+        self.0 == TypeId::of::<S>()
+    }
+}
+
+/// A run condition that checks if the state type `S` is allowed to flush by the
+/// [`StateFlushFilter`] (or if there is no filter).
+pub fn is_flush_allowed<S: 'static>(filter: Option<Res<StateFlushFilter>>) -> bool {
+    filter.is_none_or(|x| x.allows::<S>())
+}
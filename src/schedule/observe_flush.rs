@@ -0,0 +1,121 @@
+//! Trigger [`ExitState`], [`TransState`], and [`EnterState`] observer events on state flush.
+//!
+//! Use the `observe_flush` [derive macro option](pyri_state_derive::State) to opt in. Each state
+//! type that opts in adds three exclusive systems to its flush schedule.
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds observer event triggering systems for the [`State`] type `S`
+    /// to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Calls [`schedule_observe_flush<S>`].
+    pub struct ObserveFlushPlugin<S: State + Clone>(PhantomData<S>);
+
+    impl<S: State + Clone> Plugin for ObserveFlushPlugin<S> {
+        fn build(&self, app: &mut App) {
+            schedule_observe_flush::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State + Clone> Default for ObserveFlushPlugin<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+}
+
+use bevy_ecs::{
+    event::Event,
+    schedule::{IntoScheduleConfigs as _, Schedule},
+    system::SystemState,
+    world::World,
+};
+use tiny_bail::prelude::*;
+
+use crate::{
+    access::{FlushRef, NextRef},
+    schedule::ResolveStateSystems,
+    state::State,
+};
+
+/// An observer event triggered whenever the [`State`] type `S` exits (including when it becomes
+/// disabled).
+///
+/// Triggered with the `observe_flush` [derive macro option](pyri_state_derive::State) in [`ResolveStateSystems::<S>::Exit`] by
+/// [`ObserveFlushPlugin<S>`].
+///
+/// See [`StatePattern::observe_exit`](crate::pattern::StatePattern::observe_exit).
+#[derive(Event, Clone, Debug)]
+pub struct ExitState<S: State> {
+    /// The state being exited.
+    pub state: S,
+}
+
+/// An observer event triggered whenever the [`State`] type `S` transitions from an enabled
+/// state to an enabled state.
+///
+/// Triggered with the `observe_flush` [derive macro option](pyri_state_derive::State) in [`ResolveStateSystems::<S>::Trans`] by
+/// [`ObserveFlushPlugin<S>`].
+///
+/// See [`StateTransPattern::observe_trans`](crate::pattern::StateTransPattern::observe_trans).
+#[derive(Event, Clone, Debug)]
+pub struct TransState<S: State> {
+    /// The state before the transition.
+    pub old: S,
+    /// The state after the transition.
+    pub new: S,
+}
+
+/// An observer event triggered whenever the [`State`] type `S` enters (including when it
+/// becomes enabled).
+///
+/// Triggered with the `observe_flush` [derive macro option](pyri_state_derive::State) in [`ResolveStateSystems::<S>::Enter`] by
+/// [`ObserveFlushPlugin<S>`].
+///
+/// See [`StatePattern::observe_enter`](crate::pattern::StatePattern::observe_enter).
+#[derive(Event, Clone, Debug)]
+pub struct EnterState<S: State> {
+    /// The state being entered.
+    pub state: S,
+}
+
+fn trigger_exit<S: State + Clone>(world: &mut World) {
+    let state = r!(world.get_resource::<S>()).clone();
+    world.trigger(ExitState { state });
+}
+
+fn trigger_trans<S: State + Clone>(world: &mut World, state: &mut SystemState<FlushRef<S>>) {
+    let (old, new) = {
+        let state = r!(state.get(world));
+        let (old, new) = state.get();
+        (r!(old).clone(), r!(new).clone())
+    };
+    world.trigger(TransState { old, new });
+}
+
+fn trigger_enter<S: State + Clone>(world: &mut World, next: &mut SystemState<NextRef<S>>) {
+    let state = r!(r!(next.get(world)).get()).clone();
+    world.trigger(EnterState { state });
+}
+
+/// Add observer event triggering systems for the [`State`] type `S` to a schedule.
+///
+/// Used in [`ObserveFlushPlugin<S>`].
+pub fn schedule_observe_flush<S: State + Clone>(schedule: &mut Schedule) {
+    schedule.add_systems((
+        trigger_exit::<S>.in_set(ResolveStateSystems::<S>::AnyExit),
+        trigger_trans::<S>.in_set(ResolveStateSystems::<S>::AnyTrans),
+        trigger_enter::<S>.in_set(ResolveStateSystems::<S>::AnyEnter),
+    ));
+}
@@ -9,6 +9,10 @@ mod app {
 
     use bevy_app::{App, Plugin};
 
+    use crate::schedule::{
+        StateFlushSchedules, cascade_flush::schedule_cascade_flush, flush_schedule_label,
+    };
+
     use super::*;
 
     /// A plugin that configures the [`ResolveStateSystems<S>`] system sets for the [`State`]
@@ -17,7 +21,9 @@ mod app {
     /// To specify a dependency relative to another `State` type `T`, add
     /// [`ResolveStateSystems::<T>::Resolve`] to [`after`](Self::after) or [`before`](Self::before).
     ///
-    /// Calls [`schedule_resolve_state<S>`].
+    /// Initializes the [`AppliedStateFlush<S>`] resource, adds the flush schedule of `S` to the
+    /// [`StateFlushSchedules`] resource, and calls [`schedule_resolve_state<S>`] and
+    /// [`schedule_cascade_flush<S>`].
     pub struct ResolveStatePlugin<S: State> {
         after: Vec<InternedSystemSet>,
         before: Vec<InternedSystemSet>,
@@ -26,11 +32,14 @@ mod app {
 
     impl<S: State> Plugin for ResolveStatePlugin<S> {
         fn build(&self, app: &mut App) {
-            schedule_resolve_state::<S>(
-                app.get_schedule_mut(crate::schedule::StateFlush).unwrap(),
-                &self.after,
-                &self.before,
-            );
+            app.init_resource::<AppliedStateFlush<S>>();
+            let label = flush_schedule_label::<S>(app.world());
+            app.world_mut()
+                .get_resource_or_init::<StateFlushSchedules>()
+                .add(label);
+            let schedule = crate::schedule::flush_schedule_mut::<S>(app);
+            schedule_resolve_state::<S>(schedule, &self.after, &self.before);
+            schedule_cascade_flush::<S>(schedule);
         }
     }
 
@@ -70,11 +79,16 @@ mod app {
 
 use core::{convert::Infallible, fmt::Debug, hash::Hash, marker::PhantomData};
 
-use bevy_ecs::schedule::{
-    InternedSystemSet, IntoScheduleConfigs as _, Schedule, SystemCondition, SystemSet,
+use bevy_ecs::{
+    schedule::{InternedSystemSet, IntoScheduleConfigs as _, Schedule, SystemCondition, SystemSet},
+    system::{Res, ResMut},
 };
 
-use crate::{schedule::ApplyFlushSystems, state::State};
+use crate::{
+    next_state::{AppliedStateFlush, TriggerStateFlush},
+    schedule::{ApplyFlushSystems, cascade_flush::CascadeFlushSystems, is_flush_allowed},
+    state::State,
+};
 
 /// A suite of system sets in the [`StateFlush`](crate::schedule::StateFlush)
 /// schedule for each [`State`] type `S`.
@@ -83,15 +97,19 @@ use crate::{schedule::ApplyFlushSystems, state::State};
 /// [`ResolveStatePlugin<S>`] as follows:
 ///
 /// 1. [`Resolve`](Self::Resolve) (before or after other `Resolve` system sets based on
-///    state dependencies, and before [`ApplyFlushSystems`])
+///    state dependencies, before [`ApplyFlushSystems`], and if allowed by the
+///    [`StateFlushFilter`](crate::schedule::StateFlushFilter))
 ///     1. [`Compute`](Self::Compute)
 ///     2. [`Trigger`](Self::Trigger)
-///     3. [`Flush`](Self::Flush) (and [`AnyFlush`](Self::AnyFlush) if the global state will flush)
+///     3. [`Guard`](Self::Guard)
+///     4. [`Hold`](Self::Hold)
+///     5. [`Flush`](Self::Flush) (and [`AnyFlush`](Self::AnyFlush) if the global state will flush)
 ///         1. [`Exit`](Self::Exit) (and [`AnyExit`](Self::AnyExit) if the global state will exit)
 ///         2. [`Trans`](Self::Trans) (and [`AnyTrans`](Self::AnyTrans) if the global state will
 ///            transition)
 ///         3. [`Enter`](Self::Enter) (and [`AnyEnter`](Self::AnyEnter) if the global state will
 ///            enter)
+/// 2. [`Applied`](Self::Applied) (after [`ApplyFlushSystems`] if the global state was flushed)
 #[derive(SystemSet)]
 pub enum ResolveStateSystems<S: State> {
     /// Resolve the state flush logic for `S`.
@@ -100,6 +118,10 @@ pub enum ResolveStateSystems<S: State> {
     Compute,
     /// Decide whether to trigger a flush for `S`.
     Trigger,
+    /// Optionally reject a triggered flush for `S`.
+    Guard,
+    /// Optionally hold a triggered flush for `S` until a later run.
+    Hold,
     /// Run on-flush hooks for `S`.
     Flush,
     /// Run on-exit hooks for `S`.
@@ -116,6 +138,8 @@ pub enum ResolveStateSystems<S: State> {
     AnyTrans,
     /// Run global on-enter hooks for `S.
     AnyEnter,
+    /// Run post-apply hooks for `S`.
+    Applied,
     #[doc(hidden)]
     _PhantomData(PhantomData<S>, Infallible),
 }
@@ -126,6 +150,8 @@ impl<S: State> Clone for ResolveStateSystems<S> {
             Self::Resolve => Self::Resolve,
             Self::Compute => Self::Compute,
             Self::Trigger => Self::Trigger,
+            Self::Guard => Self::Guard,
+            Self::Hold => Self::Hold,
             Self::Flush => Self::Flush,
             Self::Exit => Self::Exit,
             Self::Trans => Self::Trans,
@@ -134,6 +160,7 @@ impl<S: State> Clone for ResolveStateSystems<S> {
             Self::AnyExit => Self::AnyExit,
             Self::AnyTrans => Self::AnyTrans,
             Self::AnyEnter => Self::AnyEnter,
+            Self::Applied => Self::Applied,
             Self::_PhantomData(..) => unreachable!(),
         }
     }
@@ -159,6 +186,8 @@ impl<S: State> Debug for ResolveStateSystems<S> {
             Self::Resolve => write!(f, "Resolve"),
             Self::Compute => write!(f, "Compute"),
             Self::Trigger => write!(f, "Trigger"),
+            Self::Guard => write!(f, "Guard"),
+            Self::Hold => write!(f, "Hold"),
             Self::Flush => write!(f, "Flush"),
             Self::Exit => write!(f, "Exit"),
             Self::Trans => write!(f, "Trans"),
@@ -167,11 +196,19 @@ impl<S: State> Debug for ResolveStateSystems<S> {
             Self::AnyExit => write!(f, "AnyExit"),
             Self::AnyTrans => write!(f, "AnyTrans"),
             Self::AnyEnter => write!(f, "AnyEnter"),
+            Self::Applied => write!(f, "Applied"),
             Self::_PhantomData(..) => unreachable!(),
         }
     }
 }
 
+fn record_applied<S: State>(
+    trigger: Res<TriggerStateFlush<S>>,
+    mut applied: ResMut<AppliedStateFlush<S>>,
+) {
+    applied.0 = trigger.0;
+}
+
 /// Configure [`ResolveStateSystems<S>`] for the [`State`] type `S` in a schedule.
 ///
 /// To specify a dependency relative to another `State` type `T`, include
@@ -193,12 +230,18 @@ pub fn schedule_resolve_state<S: State>(
 
     // Internal ordering
     schedule.configure_sets((
-        ResolveStateSystems::<S>::Resolve.before(ApplyFlushSystems),
+        ResolveStateSystems::<S>::Resolve
+            .before(ApplyFlushSystems)
+            .run_if(is_flush_allowed::<S>),
         (
             ResolveStateSystems::<S>::Compute,
             // Logic in this system set should only run if not triggered.
             ResolveStateSystems::<S>::Trigger,
             // Logic in this system set should only run if triggered.
+            ResolveStateSystems::<S>::Guard,
+            // Logic in this system set should only run if triggered.
+            ResolveStateSystems::<S>::Hold,
+            // Logic in this system set should only run if triggered.
             ResolveStateSystems::<S>::Flush,
         )
             .chain()
@@ -225,5 +268,117 @@ pub fn schedule_resolve_state<S: State>(
                 .in_set(ResolveStateSystems::<S>::Enter),
         )
             .in_set(ResolveStateSystems::<S>::AnyFlush),
+        ResolveStateSystems::<S>::Applied
+            .after(ApplyFlushSystems)
+            .before(CascadeFlushSystems)
+            .run_if(is_flush_allowed::<S>.and_then(S::was_applied)),
     ));
+
+    // Remember whether the flush will be applied for the `Applied` system set. This also marks
+    // `AppliedStateFlush<S>` as changed at the end of `Resolve`, which cascading change detection
+    // relies on.
+    schedule.add_systems(
+        record_applied::<S>
+            .after(ResolveStateSystems::<S>::Flush)
+            .in_set(ResolveStateSystems::<S>::Resolve),
+    );
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use bevy_app::{App, Update};
+    use bevy_ecs::resource::Resource;
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    use super::*;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Level(usize);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Score(usize);
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .init_state::<Level>()
+            .init_resource::<Log>();
+        app.update();
+        app
+    }
+
+    fn log(app: &App) -> &[&'static str] {
+        &app.world().resource::<Log>().0
+    }
+
+    #[test]
+    fn applied_hooks_see_new_state() {
+        let mut app = app();
+        app.add_systems(
+            StateFlush,
+            (
+                Level(0).on_exited(|level: Res<Level>, mut log: ResMut<Log>| {
+                    assert_eq!(*level, Level(1));
+                    log.0.push("exited 0");
+                }),
+                Level(1).on_entered(|level: Res<Level>, mut log: ResMut<Log>| {
+                    assert_eq!(*level, Level(1));
+                    log.0.push("entered 1");
+                }),
+                Level(1).on_exited(|mut log: ResMut<Log>| log.0.push("exited 1")),
+            ),
+        );
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        app.update();
+        let mut log = log(&app).to_vec();
+        log.sort();
+        assert_eq!(log, ["entered 1", "exited 0"]);
+    }
+
+    #[test]
+    fn applied_hooks_run_once_per_cascade() {
+        let mut app = app();
+        app.add_plugins(StateFlushPlugin::default().cascade(4))
+            .init_state::<Score>()
+            .add_systems(
+                StateFlush,
+                Level(1).on_entered(|mut score: NextMut<Score>, mut log: ResMut<Log>| {
+                    score.enter(Score(1));
+                    log.0.push("entered 1");
+                }),
+            );
+        app.update();
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        assert_eq!(app.world().state::<Score>(), Some(&Score(1)));
+        assert_eq!(log(&app), ["entered 1"]);
+    }
+
+    #[test]
+    fn was_applied_without_insert_state() {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin)
+            .add_state::<Score>()
+            .init_resource::<Log>()
+            .add_systems(
+                Update,
+                (|mut log: ResMut<Log>| log.0.push("applied")).run_if(Score::was_applied),
+            );
+
+        app.update();
+        assert!(log(&app).is_empty());
+
+        app.world_mut().set_next_state(Some(Score(1)));
+        app.update();
+        assert_eq!(log(&app), ["applied"]);
+    }
 }
@@ -0,0 +1,64 @@
+//! Apply deferred commands between the exit, transition, and enter phases of a state flush.
+//!
+//! Bevy inserts sync points automatically where systems with [`Commands`] are ordered before
+//! other systems, but only if [auto-insertion](bevy_ecs::schedule::ScheduleBuildSettings) is
+//! enabled and the ordering doesn't ignore deferred commands. These sync points are explicit, so
+//! on-enter hooks will always see the effects of on-exit hooks (e.g. despawned entities).
+//!
+//! [`Commands`]: bevy_ecs::system::Commands
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::flush_schedule_mut;
+
+    use super::*;
+
+    /// A plugin that adds sync points between the flush phases of the [`State`] type `S`
+    /// in the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Calls [`schedule_sync_flush<S>`].
+    pub struct SyncFlushPlugin<S: State>(PhantomData<S>);
+
+    impl<S: State> Plugin for SyncFlushPlugin<S> {
+        fn build(&self, app: &mut App) {
+            schedule_sync_flush::<S>(flush_schedule_mut::<S>(app));
+        }
+    }
+
+    impl<S: State> Default for SyncFlushPlugin<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+}
+
+use bevy_ecs::schedule::{ApplyDeferred, IntoScheduleConfigs as _, Schedule};
+
+use crate::{schedule::ResolveStateSystems, state::State};
+
+/// Add [`ApplyDeferred`] sync points for the [`State`] type `S` to a schedule.
+///
+/// One sync point runs between [`ResolveStateSystems::<S>::Exit`] and
+/// [`ResolveStateSystems::<S>::Trans`], and another runs between `Trans` and
+/// [`ResolveStateSystems::<S>::Enter`].
+///
+/// Used in [`SyncFlushPlugin<S>`].
+pub fn schedule_sync_flush<S: State>(schedule: &mut Schedule) {
+    schedule.add_systems((
+        ApplyDeferred
+            .after(ResolveStateSystems::<S>::Exit)
+            .before(ResolveStateSystems::<S>::Trans)
+            .in_set(ResolveStateSystems::<S>::AnyFlush),
+        ApplyDeferred
+            .after(ResolveStateSystems::<S>::Trans)
+            .before(ResolveStateSystems::<S>::Enter)
+            .in_set(ResolveStateSystems::<S>::AnyFlush),
+    ));
+}
@@ -0,0 +1,128 @@
+//! Reject any state transition that doesn't match a table of allowed transitions.
+
+#[cfg(feature = "bevy_app")]
+pub use app::*;
+
+#[cfg(feature = "bevy_app")]
+mod app {
+    use core::marker::PhantomData;
+
+    use bevy_app::{App, Plugin};
+
+    use crate::schedule::{flush_schedule_mut, guard_flush::StateFlushRejected};
+
+    use super::*;
+
+    /// A plugin that adds a transition validation system for the [`State`](crate::state::State) type `S`
+    /// to the [`StateFlush`](crate::schedule::StateFlush) schedule.
+    ///
+    /// Added by the `transitions(...)` [derive macro option](pyri_state_derive::State).
+    ///
+    /// Calls [`schedule_validate_trans<S>`].
+    pub struct ValidateTransPlugin<S: StateMut + Clone + Debug, P: StateTransPattern<S> + Clone> {
+        allowed: P,
+        _phantom: PhantomData<S>,
+    }
+
+    impl<S: StateMut + Clone + Debug, P: StateTransPattern<S> + Clone> Plugin
+        for ValidateTransPlugin<S, P>
+    {
+        fn build(&self, app: &mut App) {
+            app.add_message::<StateFlushRejected<S>>();
+            schedule_validate_trans::<S, P>(flush_schedule_mut::<S>(app), self.allowed.clone());
+        }
+    }
+
+    impl<S: StateMut + Clone + Debug, P: StateTransPattern<S> + Clone> ValidateTransPlugin<S, P> {
+        /// Create a [`ValidateTransPlugin`] from a pattern matching the allowed transitions.
+        pub fn new(allowed: P) -> Self {
+            Self {
+                allowed,
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+use alloc::format;
+use core::{any::type_name, fmt::Debug};
+
+use bevy_ecs::{
+    schedule::{IntoScheduleConfigs as _, Schedule, SystemCondition as _},
+    system::IntoSystem,
+};
+
+use crate::{
+    access::FlushRef, pattern::StateTransPattern, schedule::guard_flush::guard_flush,
+    state::StateMut,
+};
+
+/// Add a transition validation system for the [`State`](crate::state::State) type `S` to a schedule.
+///
+/// Any transition that doesn't match the `allowed` pattern will be rejected as if by a
+/// [guard](crate::schedule::guard_flush), writing a
+/// [`StateFlushRejected<S>`](crate::schedule::guard_flush::StateFlushRejected) message and
+/// logging a warning. Becoming enabled or disabled is always allowed.
+///
+/// Used in [`ValidateTransPlugin<S, P>`].
+pub fn schedule_validate_trans<S: StateMut + Clone + Debug, P: StateTransPattern<S>>(
+    schedule: &mut Schedule,
+    allowed: P,
+) {
+    let validate_trans = IntoSystem::into_system(move |state: FlushRef<S>| {
+        let is_allowed = state.will_trans(&allowed);
+        if !is_allowed {
+            let ty = type_name::<S>();
+            let (old, new) = state.unwrap();
+            bevy_log::warn!("{ty} blocked illegal transition: {old:?} -> {new:?}");
+        }
+        is_allowed
+    })
+    .with_name(format!("{}::validate_trans", type_name::<S>()));
+
+    schedule.add_systems(
+        guard_flush::<S, _>(validate_trans).run_if(S::is_enabled.and_then(S::will_be_enabled)),
+    );
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use bevy_app::App;
+    use bevy_ecs::{message::Messages, resource::Resource};
+
+    use crate::{prelude::*, setup::WorldExtState as _};
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+    #[state(transitions(Level(0) => Level(1), Level(1) => Level(2)))]
+    struct Level(usize);
+
+    #[test]
+    fn illegal_transition_is_rejected() {
+        let mut app = App::new();
+        app.add_plugins(StatePlugin).add_state::<Level>();
+
+        app.world_mut().set_next_state(Some(Level(0)));
+        app.update();
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(0)));
+        assert_eq!(app.world_mut().next_state::<Level>(), Some(Level(0)));
+
+        let rejected = app
+            .world_mut()
+            .resource_mut::<Messages<StateFlushRejected<Level>>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].old, Some(Level(0)));
+        assert_eq!(rejected[0].new, Some(Level(2)));
+
+        app.world_mut().set_next_state(Some(Level(1)));
+        app.update();
+        app.world_mut().set_next_state(Some(Level(2)));
+        app.update();
+        assert_eq!(app.world().state::<Level>(), Some(&Level(2)));
+    }
+}
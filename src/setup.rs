@@ -8,10 +8,16 @@ pub use app::*;
 
 #[cfg(feature = "bevy_app")]
 mod app {
-    use bevy_app::{App, MainScheduleOrder, Plugin, PreUpdate};
+    use alloc::vec::Vec;
+
+    use bevy_app::{App, FixedMainScheduleOrder, MainScheduleOrder, Plugin, PreUpdate};
+    use bevy_ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
     use tiny_bail::prelude::*;
 
-    use crate::schedule::StateFlush;
+    use crate::schedule::{
+        StateFlush,
+        cascade_flush::{CascadeStateFlush, StateFlushCascade, cascade_state_flush},
+    };
 
     use super::*;
 
@@ -20,6 +26,8 @@ mod app {
     /// - Adds the [`StateFlush`] schedule to the [`MainScheduleOrder`] before [`PreUpdate`].
     /// - Adds the [`bevy_state` plugin](bevy_state::app::StatesPlugin) if the
     ///   `bevy_state` feature is enabled.
+    ///
+    /// Add [`StateFlushPlugin`] after this plugin to configure the `StateFlush` schedule further.
     pub struct StatePlugin;
 
     impl Plugin for StatePlugin {
@@ -37,6 +45,136 @@ mod app {
         }
     }
 
+    /// A plugin that configures when the [`StateFlush`] schedule runs:
+    ///
+    /// - Moves the `StateFlush` schedule before a different schedule in the
+    ///   [`MainScheduleOrder`] (configurable with [`flush_before`](Self::flush_before)).
+    /// - Adds the `StateFlush` schedule after any
+    ///   [extra flush points](Self::also_flush_after).
+    /// - Adds the [`CascadeStateFlush`] schedule after `StateFlush` if
+    ///   [cascading](Self::cascade) is enabled.
+    ///
+    /// This plugin must be added after [`StatePlugin`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use pyri_state::prelude::*;
+    /// #
+    /// # fn plugin(app: &mut App) {
+    /// app.add_plugins((StatePlugin, StateFlushPlugin::default().cascade(8)));
+    /// # }
+    /// ```
+    pub struct StateFlushPlugin {
+        cascade: Option<usize>,
+        flush_before: InternedScheduleLabel,
+        also_flush_after: Vec<InternedScheduleLabel>,
+    }
+
+    impl Default for StateFlushPlugin {
+        fn default() -> Self {
+            Self {
+                cascade: None,
+                flush_before: PreUpdate.intern(),
+                also_flush_after: Vec::new(),
+            }
+        }
+    }
+
+    impl Plugin for StateFlushPlugin {
+        fn build(&self, app: &mut App) {
+            let mut main_order = r!(app.world_mut().get_resource_mut::<MainScheduleOrder>());
+
+            // Move the `StateFlush` schedule.
+            if self.flush_before != PreUpdate.intern() {
+                if main_order.labels.contains(&self.flush_before) {
+                    main_order.labels.retain(|&x| x != StateFlush.intern());
+                    let index = r!(main_order
+                        .labels
+                        .iter()
+                        .position(|&x| x == self.flush_before));
+                    main_order.labels.insert(index, StateFlush.intern());
+                } else {
+                    bevy_log::warn!(
+                        "Could not move {StateFlush:?} before {:?}: schedule not found in the \
+                        main schedule order",
+                        self.flush_before,
+                    );
+                }
+            }
+
+            // Add the `CascadeStateFlush` schedule.
+            if let Some(max_iterations) = self.cascade {
+                let index = r!(main_order
+                    .labels
+                    .iter()
+                    .position(|&x| x == StateFlush.intern()));
+                main_order
+                    .labels
+                    .insert(index + 1, CascadeStateFlush.intern());
+                app.insert_resource(StateFlushCascade::new(max_iterations))
+                    .add_systems(CascadeStateFlush, cascade_state_flush);
+            }
+
+            // Add extra flush points.
+            let world = app.world_mut();
+            for &label in &self.also_flush_after {
+                if let Some(mut main_order) = world.get_resource_mut::<MainScheduleOrder>()
+                    && let Some(index) = main_order.labels.iter().position(|&x| x == label)
+                {
+                    main_order.labels.insert(index + 1, StateFlush.intern());
+                    continue;
+                }
+
+                if let Some(mut fixed_order) = world.get_resource_mut::<FixedMainScheduleOrder>()
+                    && let Some(index) = fixed_order.labels.iter().position(|&x| x == label)
+                {
+                    fixed_order.labels.insert(index + 1, StateFlush.intern());
+                    continue;
+                }
+
+                bevy_log::warn!(
+                    "Could not add a {StateFlush:?} flush point after {label:?}: schedule not \
+                    found in the main or fixed main schedule order",
+                );
+            }
+        }
+    }
+
+    impl StateFlushPlugin {
+        /// Re-run the [`StateFlush`] schedule until no [`State`] type is triggered to flush,
+        /// up to `max_iterations` runs in total.
+        ///
+        /// This allows on-flush hooks to trigger flushes of states that have already resolved,
+        /// so that chains of dependent states settle within a single frame.
+        ///
+        /// See [`StateFlushCascade`] for how the iteration limit is reported.
+        pub fn cascade(mut self, max_iterations: usize) -> Self {
+            self.cascade = Some(max_iterations);
+            self
+        }
+
+        /// Run the [`StateFlush`] schedule before `label` in the [`MainScheduleOrder`]
+        /// instead of before [`PreUpdate`].
+        pub fn flush_before(mut self, label: impl ScheduleLabel) -> Self {
+            self.flush_before = label.intern();
+            self
+        }
+
+        /// Also run the [`StateFlush`] schedule after `label` in the [`MainScheduleOrder`] or
+        /// [`FixedMainScheduleOrder`].
+        ///
+        /// For example, `also_flush_after(FixedUpdate)` allows states that drive fixed-timestep
+        /// gameplay to flush between fixed timesteps.
+        ///
+        /// [`CascadeStateFlush`] will not run after extra flush points.
+        pub fn also_flush_after(mut self, label: impl ScheduleLabel) -> Self {
+            self.also_flush_after.push(label.intern());
+            self
+        }
+    }
+
     /// An extension trait for [`App`] that provides methods for adding [`State`] types.
     pub trait AppExtState {
         /// Register a `State` type without initializing it.
@@ -96,13 +234,23 @@ mod app {
 }
 
 use bevy_ecs::{
-    system::Commands,
+    system::{Commands, In, SystemState},
     world::{FromWorld, World},
 };
+use tiny_bail::prelude::*;
 
+#[cfg(feature = "history")]
+use crate::extra::history::StateMutExtHistory as _;
 use crate::{
-    next_state::{NextState, TriggerStateFlush},
+    access::{FlushMut, NextMut, NextRef},
+    next_state::{AppliedStateFlush, NextState, TriggerStateFlush},
     prelude::State,
+    schedule::{
+        StateFlush, StateFlushFilter, StateFlushSchedules,
+        cascade_flush::{CascadeStateFlush, StateFlushCascade},
+        flush_schedule_label,
+    },
+    state::{StateMut, StateMutExtClone as _},
 };
 
 fn state_exists<S: State>(world: &World) -> bool {
@@ -112,9 +260,11 @@ fn state_exists<S: State>(world: &World) -> bool {
 fn insert_state<Next: NextState>(world: &mut World, next: Option<Next>) {
     world.insert_resource(next.unwrap_or_else(Next::empty));
     world.init_resource::<TriggerStateFlush<Next::State>>();
+    world.init_resource::<AppliedStateFlush<Next::State>>();
 }
 
-/// An extension trait for [`Commands`] that provides methods for adding [`State`] types.
+/// An extension trait for [`Commands`] that provides methods for adding and updating [`State`]
+/// types.
 pub trait CommandsExtState {
     /// Queue a command to initialize a `State` type with an empty `NextState`.
     ///
@@ -126,6 +276,52 @@ pub trait CommandsExtState {
 
     /// Queue a command to initialize a `State` type with a specific `NextState`.
     fn insert_state<T: NextState>(&mut self, next: T);
+
+    /// Queue a command to set the next state to a new value, or `None` to disable.
+    ///
+    /// Calls [`NextMut::set`].
+    fn set_state<S: StateMut>(&mut self, state: Option<S>);
+
+    /// Queue a command to enable the next state with a specific value.
+    ///
+    /// Calls [`NextMut::enter`].
+    fn enter_state<S: StateMut>(&mut self, value: S);
+
+    /// Queue a command to disable the next state.
+    ///
+    /// Calls [`NextMut::disable`].
+    fn disable_state<S: StateMut>(&mut self);
+
+    /// Queue a command to set the next state to a toggle of the current state between disabled
+    /// and enabled with a specific value.
+    ///
+    /// Calls [`FlushMut::toggle`].
+    fn toggle_state<S: StateMut>(&mut self, value: S);
+
+    /// Queue a command to reset the next state to the current state and trigger a flush.
+    ///
+    /// Calls [`FlushMut::refresh`].
+    fn refresh_state<S: StateMut + Clone>(&mut self);
+
+    /// Queue a command to reset the next state to the current state and reset the trigger to
+    /// flush.
+    ///
+    /// Calls [`FlushMut::reset`].
+    fn reset_state<S: StateMut + Clone>(&mut self);
+
+    /// Queue a command to undo the most recent record in
+    /// [`StateHistory<S>`](crate::extra::history::StateHistory).
+    ///
+    /// Calls [`S::undo`](crate::extra::history::StateMutExtHistory::undo).
+    #[cfg(feature = "history")]
+    fn undo_state<S: StateMut + Clone + Eq>(&mut self);
+
+    /// Queue a command to redo the most recently undone record in
+    /// [`StateHistory<S>`](crate::extra::history::StateHistory).
+    ///
+    /// Calls [`S::redo`](crate::extra::history::StateMutExtHistory::redo).
+    #[cfg(feature = "history")]
+    fn redo_state<S: StateMut + Clone + Eq>(&mut self);
 }
 
 impl CommandsExtState for Commands<'_, '_> {
@@ -149,4 +345,229 @@ impl CommandsExtState for Commands<'_, '_> {
     fn insert_state<T: NextState>(&mut self, next: T) {
         self.queue(|world: &mut World| insert_state(world, Some(next)));
     }
+
+    fn set_state<S: StateMut>(&mut self, state: Option<S>) {
+        self.run_system_cached_with(
+            |In(state): In<Option<S>>, mut next: NextMut<S>| next.set(state),
+            state,
+        );
+    }
+
+    fn enter_state<S: StateMut>(&mut self, value: S) {
+        self.run_system_cached_with(
+            |In(value): In<S>, mut next: NextMut<S>| next.enter(value),
+            value,
+        );
+    }
+
+    fn disable_state<S: StateMut>(&mut self) {
+        self.run_system_cached(S::disable);
+    }
+
+    fn toggle_state<S: StateMut>(&mut self, value: S) {
+        self.run_system_cached_with(
+            |In(value): In<S>, mut state: FlushMut<S>| state.toggle(value),
+            value,
+        );
+    }
+
+    fn refresh_state<S: StateMut + Clone>(&mut self) {
+        self.run_system_cached(S::refresh);
+    }
+
+    fn reset_state<S: StateMut + Clone>(&mut self) {
+        self.run_system_cached(S::reset);
+    }
+
+    #[cfg(feature = "history")]
+    fn undo_state<S: StateMut + Clone + Eq>(&mut self) {
+        self.run_system_cached(S::undo);
+    }
+
+    #[cfg(feature = "history")]
+    fn redo_state<S: StateMut + Clone + Eq>(&mut self) {
+        self.run_system_cached(S::redo);
+    }
+}
+
+/// An extension trait for [`World`] that provides methods for accessing and flushing [`State`]
+/// types directly.
+///
+/// This is useful for headless servers and tests that drive a `World` directly, and does not
+/// require the `bevy_app` feature.
+///
+/// ```
+/// # use bevy::{ecs::schedule::Schedule, prelude::*};
+/// use pyri_state::{
+///     prelude::*,
+///     schedule::{apply_flush::schedule_apply_flush, resolve_state::schedule_resolve_state},
+/// };
+///
+/// #[derive(State, Resource, Clone, PartialEq, Eq, Debug)]
+/// struct Level(usize);
+///
+/// let mut world = World::new();
+/// let mut schedule = Schedule::new(StateFlush);
+/// schedule_resolve_state::<Level>(&mut schedule, &[], &[]);
+/// schedule_apply_flush::<Level>(&mut schedule);
+/// world.add_schedule(schedule);
+/// world.commands().add_state::<Level>();
+/// world.flush();
+///
+/// world.set_next_state(Some(Level(1)));
+/// world.trigger_state::<Level>();
+/// world.flush_state::<Level>();
+/// assert_eq!(world.state::<Level>(), Some(&Level(1)));
+/// ```
+pub trait WorldExtState {
+    /// Get a reference to the current state, or `None` if disabled.
+    fn state<S: State>(&self) -> Option<&S>;
+
+    /// Get a clone of the next state, or `None` if disabled.
+    fn next_state<S: State + Clone>(&mut self) -> Option<S>;
+
+    /// Set the next state to a new value, or `None` to disable.
+    fn set_next_state<S: StateMut>(&mut self, state: Option<S>);
+
+    /// Trigger the `State` type `S` to flush.
+    fn trigger_state<S: State>(&mut self);
+
+    /// Run the flush schedule of the `State` type `S`, skipping the flush logic of every other
+    /// `State` type.
+    ///
+    /// Only [`ResolveStateSystems<S>`](crate::schedule::ResolveStateSystems) and the apply flush
+    /// systems for `S` will run, so the triggered flushes of other `State` types will remain
+    /// pending.
+    fn flush_state<S: State>(&mut self);
+
+    /// Run the [`StateFlush`] schedule to flush every triggered `State` type.
+    ///
+    /// If [cascading](crate::setup::StateFlushPlugin::cascade) is enabled, this also runs the
+    /// [`CascadeStateFlush`] schedule. Afterwards, every schedule listed in the
+    /// [`StateFlushSchedules`] resource (the `flush_in(...)` schedules) is run in full, including
+    /// any other systems in those schedules.
+    fn flush_all_states(&mut self);
+}
+
+impl WorldExtState for World {
+    fn state<S: State>(&self) -> Option<&S> {
+        self.get_resource::<S>()
+    }
+
+    fn next_state<S: State + Clone>(&mut self) -> Option<S> {
+        let mut next = SystemState::<NextRef<S>>::new(self);
+        r!(None, next.get(self)).get().cloned()
+    }
+
+    fn set_next_state<S: StateMut>(&mut self, state: Option<S>) {
+        let mut next = SystemState::<NextMut<S>>::new(self);
+        r!(next.get_mut(self)).set(state);
+        next.apply(self);
+    }
+
+    fn trigger_state<S: State>(&mut self) {
+        r!(self.get_resource_mut::<TriggerStateFlush<S>>()).0 = true;
+    }
+
+    fn flush_state<S: State>(&mut self) {
+        let label = flush_schedule_label::<S>(self);
+        self.insert_resource(StateFlushFilter::new::<S>());
+        self.run_schedule(label);
+        self.remove_resource::<StateFlushFilter>();
+    }
+
+    fn flush_all_states(&mut self) {
+        self.run_schedule(StateFlush);
+        if self.contains_resource::<StateFlushCascade>() {
+            self.run_schedule(CascadeStateFlush);
+        }
+
+        let labels = self
+            .get_resource::<StateFlushSchedules>()
+            .map(|x| x.labels().to_vec())
+            .unwrap_or_default();
+        for label in labels {
+            self.run_schedule(label);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bevy_app"))]
+mod tests {
+    use alloc::vec;
+
+    use bevy_app::{App, MainScheduleOrder};
+    use bevy_ecs::{resource::Resource, schedule::ScheduleLabel};
+
+    use crate::{
+        pattern::StatePattern as _,
+        schedule::cascade_flush::{CascadeStateFlush, StateFlushCascade},
+    };
+
+    use super::*;
+
+    #[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
+    struct Missing;
+
+    #[test]
+    fn missing_flush_point_is_skipped() {
+        let mut app = App::new();
+        app.add_plugins((
+            StatePlugin,
+            StateFlushPlugin::default()
+                .flush_before(Missing)
+                .also_flush_after(Missing)
+                .cascade(2),
+        ));
+
+        let labels = &app.world().resource::<MainScheduleOrder>().labels;
+        let index = labels.iter().position(|&x| x == StateFlush.intern());
+        assert!(index.is_some());
+        assert_eq!(
+            labels.iter().filter(|&&x| x == StateFlush.intern()).count(),
+            1,
+        );
+        assert_eq!(labels[index.unwrap() + 1], CascadeStateFlush.intern());
+        assert!(app.world().contains_resource::<StateFlushCascade>());
+    }
+
+    #[derive(ScheduleLabel, Clone, Hash, PartialEq, Eq, Debug)]
+    struct Custom;
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    struct Leader(usize);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(before(Leader))]
+    struct Follower(usize);
+
+    #[derive(State, Resource, Clone, PartialEq, Eq, Debug, Default)]
+    #[state(flush_in(Custom))]
+    struct Remote(usize);
+
+    #[test]
+    fn flush_all_states_cascades_and_runs_other_flush_schedules() {
+        let mut app = App::new();
+        app.add_plugins((StatePlugin, StateFlushPlugin::default().cascade(4)))
+            .init_state::<Leader>()
+            .init_state::<Follower>()
+            .init_state::<Remote>()
+            .add_systems(
+                StateFlush,
+                Leader::ANY.on_enter(|In(leader): In<Leader>, mut next: NextMut<Follower>| {
+                    next.enter(Follower(leader.0));
+                }),
+            );
+        let world = app.world_mut();
+        world.flush_all_states();
+        assert_eq!(world.state::<Remote>(), Some(&Remote(0)));
+
+        world.set_next_state(Some(Leader(1)));
+        world.set_next_state(Some(Remote(1)));
+        world.trigger_state::<Remote>();
+        world.flush_all_states();
+        assert_eq!(world.state::<Leader>(), Some(&Leader(1)));
+        assert_eq!(world.state::<Follower>(), Some(&Follower(1)));
+        assert_eq!(world.state::<Remote>(), Some(&Remote(1)));
+    }
 }
@@ -10,8 +10,11 @@ use bevy_ecs::{
 
 use crate::{
     access::{CurrentRef, FlushMut, FlushRef, NextMut, NextRef},
-    next_state::{NextState, NextStateMut, TriggerStateFlush},
-    pattern::{AnyStatePattern, AnyStateTransPattern, FnStatePattern, FnStateTransPattern},
+    next_state::{AppliedStateFlush, NextState, NextStateMut, TriggerStateFlush},
+    pattern::{
+        AnyStatePattern, AnyStateTransPattern, FnStateFlushPattern, FnStatePattern,
+        FnStateTransPattern,
+    },
 };
 
 /// A [`Resource`] that can be used as a state.
@@ -67,6 +70,14 @@ pub trait State: Resource<Mutability = Mutable> + Sized {
         FnStateTransPattern::new(f)
     }
 
+    /// Create a [`FnStateFlushPattern`] from a callback.
+    fn when_flush<F>(f: F) -> FnStateFlushPattern<Self, F>
+    where
+        F: 'static + Send + Sync + Fn(Option<&Self>, Option<&Self>) -> bool,
+    {
+        FnStateFlushPattern::new(f)
+    }
+
     /// A run condition that checks if the current state is disabled.
     fn is_disabled(state: CurrentRef<Self>) -> bool {
         state.is_disabled()
@@ -93,6 +104,12 @@ pub trait State: Resource<Mutability = Mutable> + Sized {
         trigger.0
     }
 
+    /// A run condition that checks if this state type applied a flush in the latest run of the
+    /// [`StateFlush`](crate::schedule::StateFlush) schedule.
+    fn was_applied(applied: Res<AppliedStateFlush<Self>>) -> bool {
+        applied.0
+    }
+
     /// A system that triggers this state type to flush in the
     /// [`StateFlush`](crate::schedule::StateFlush) schedule.
     fn trigger(mut trigger: ResMut<TriggerStateFlush<Self>>) {